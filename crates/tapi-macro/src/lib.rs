@@ -7,77 +7,231 @@ use syn::parse_macro_input;
 #[derive(Debug)]
 struct Args {
     path: String,
-    method: Ident,
+    methods: Vec<Ident>,
+    name: Option<String>,
+    group: Option<String>,
+    state: Option<syn::Path>,
+    responses: Vec<ResponseArg>,
+    tracing: Option<TracingArg>,
+}
+
+/// The `tracing(name = "...", fields(...))` argument, where both are optional.
+#[derive(Debug, Default)]
+struct TracingArg {
+    name: Option<String>,
+    fields: proc_macro2::TokenStream,
+}
+
+/// A `200 = Json<User>` entry of `responses(...)`.
+#[derive(Debug)]
+struct ResponseArg {
+    status: syn::LitInt,
+    ty: syn::Type,
+}
+
+impl syn::parse::Parse for ResponseArg {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let status = input.parse()?;
+        input.parse::<syn::Token![=]>()?;
+        let ty = input.parse()?;
+        Ok(ResponseArg { status, ty })
+    }
+}
+
+/// The methods of `tapi::endpoints::Method`, as accepted by `method = ...`.
+const METHODS: &[&str] = &[
+    "Get", "Post", "Put", "Delete", "Patch", "Head", "Options", "Trace",
+];
+
+/// The value of a `key = "..."` argument.
+fn lit_str(key: &str, value: &syn::Expr) -> syn::Result<String> {
+    match value {
+        syn::Expr::Lit(syn::ExprLit {
+            lit: syn::Lit::Str(lit_str),
+            ..
+        }) => Ok(lit_str.value()),
+        _ => Err(syn::Error::new_spanned(
+            value,
+            format!("expected a string, as in `{key} = \"...\"`"),
+        )),
+    }
+}
+
+/// A `Get` in `method = Get` or `method = [Get, Head]`.
+fn method(expr: &syn::Expr) -> syn::Result<Ident> {
+    match expr {
+        syn::Expr::Path(syn::ExprPath { path, .. })
+            if path
+                .get_ident()
+                .is_some_and(|ident| METHODS.contains(&ident.to_string().as_str())) =>
+        {
+            Ok(path.get_ident().unwrap().clone())
+        }
+        _ => Err(syn::Error::new_spanned(
+            expr,
+            format!("unknown method, expected one of {}", METHODS.join(", ")),
+        )),
+    }
 }
 
 impl syn::parse::Parse for Args {
     fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
-        syn::punctuated::Punctuated::<syn::Meta, syn::Token![,]>::parse_terminated(input).map(
-            |punctuated| {
-                let mut path = None;
-                let mut method = None;
-                for meta in punctuated {
-                    match meta {
-                        syn::Meta::NameValue(syn::MetaNameValue {
-                            path: syn::Path { segments, .. },
-                            value,
-                            ..
-                        }) => {
-                            let ident = segments.first().unwrap().ident.to_string();
-                            match ident.as_str() {
-                                "path" => {
-                                    path = {
-                                        match value {
-                                            syn::Expr::Lit(syn::ExprLit {
-                                                lit: syn::Lit::Str(lit_str),
-                                                ..
-                                            }) => Some(lit_str.value()),
-                                            _ => panic!("unknown attribute"),
-                                        }
-                                    }
-                                }
-                                "method" => {
-                                    method = {
-                                        match value {
-                                            syn::Expr::Path(syn::ExprPath { path, .. }) => {
-                                                Some(path.segments.first().unwrap().ident.clone())
-                                            }
-                                            _ => panic!("unknown attribute"),
-                                        }
-                                    }
-                                }
-                                _ => panic!("unknown attribute"),
+        let punctuated =
+            syn::punctuated::Punctuated::<syn::Meta, syn::Token![,]>::parse_terminated(input)?;
+        let mut path = None;
+        let mut methods = Vec::new();
+        let mut name = None;
+        let mut group = None;
+        let mut state = None;
+        let mut responses = Vec::new();
+        let mut tracing = None;
+        for meta in punctuated {
+            match &meta {
+                syn::Meta::NameValue(syn::MetaNameValue { path: key, value, .. })
+                    if key.is_ident("path") =>
+                {
+                    path = Some(lit_str("path", value)?);
+                }
+                syn::Meta::NameValue(syn::MetaNameValue { path: key, value, .. })
+                    if key.is_ident("name") =>
+                {
+                    name = Some(lit_str("name", value)?);
+                }
+                syn::Meta::NameValue(syn::MetaNameValue { path: key, value, .. })
+                    if key.is_ident("group") =>
+                {
+                    group = Some(lit_str("group", value)?);
+                }
+                syn::Meta::NameValue(syn::MetaNameValue { path: key, value, .. })
+                    if key.is_ident("state") =>
+                {
+                    state = match value {
+                        syn::Expr::Path(syn::ExprPath { path, .. }) => Some(path.clone()),
+                        _ => {
+                            return Err(syn::Error::new_spanned(
+                                value,
+                                "expected a type, as in `state = AppState`",
+                            ))
+                        }
+                    };
+                }
+                syn::Meta::NameValue(syn::MetaNameValue { path: key, value, .. })
+                    if key.is_ident("method") =>
+                {
+                    methods = match value {
+                        syn::Expr::Array(array) => {
+                            array.elems.iter().map(method).collect::<syn::Result<_>>()?
+                        }
+                        value => vec![method(value)?],
+                    };
+                    if methods.is_empty() {
+                        return Err(syn::Error::new_spanned(value, "expected at least one method"));
+                    }
+                }
+                syn::Meta::List(list) if list.path.is_ident("responses") => {
+                    responses = list
+                        .parse_args_with(
+                            syn::punctuated::Punctuated::<ResponseArg, syn::Token![,]>::parse_terminated,
+                        )?
+                        .into_iter()
+                        .collect();
+                }
+                syn::Meta::Path(p) if p.is_ident("tracing") => {
+                    tracing = Some(TracingArg::default());
+                }
+                syn::Meta::List(list) if list.path.is_ident("tracing") => {
+                    let mut arg = TracingArg::default();
+                    let nested = list.parse_args_with(
+                        syn::punctuated::Punctuated::<syn::Meta, syn::Token![,]>::parse_terminated,
+                    )?;
+                    for meta in nested {
+                        match meta {
+                            syn::Meta::NameValue(syn::MetaNameValue { path, value, .. })
+                                if path.is_ident("name") =>
+                            {
+                                arg.name = Some(lit_str("name", &value)?);
+                            }
+                            syn::Meta::List(list) if list.path.is_ident("fields") => {
+                                arg.fields = list.tokens;
+                            }
+                            meta => {
+                                return Err(syn::Error::new_spanned(
+                                    meta,
+                                    "unknown `tracing` argument, expected `name = \"...\"` or `fields(...)`",
+                                ))
                             }
                         }
-                        _ => panic!("unknown attribute"),
                     }
+                    tracing = Some(arg);
                 }
-                Args {
-                    path: path.unwrap(),
-                    method: method.unwrap(),
+                meta => {
+                    return Err(syn::Error::new_spanned(
+                        meta,
+                        "unknown argument, expected one of `path`, `method`, `name`, `group`, `state`, `responses` or `tracing`",
+                    ))
                 }
+            }
+        }
+        let missing = |arg: &str| {
+            syn::Error::new(
+                proc_macro2::Span::call_site(),
+                format!("missing `{arg}`, as in `#[tapi(path = \"/users\", method = Get)]`"),
+            )
+        };
+        Ok(Args {
+            path: path.ok_or_else(|| missing("path"))?,
+            methods: if methods.is_empty() {
+                return Err(missing("method"));
+            } else {
+                methods
             },
-        )
+            name,
+            group,
+            state,
+            responses,
+            tracing,
+        })
     }
 }
 
+/// Turns an axum handler into an endpoint in a module of the same name.
 #[proc_macro_attribute]
 pub fn tapi(
     attr: proc_macro::TokenStream,
     item: proc_macro::TokenStream,
 ) -> proc_macro::TokenStream {
-    let item = proc_macro2::TokenStream::from(item);
-
-    let Args { path, method } = parse_macro_input!(attr as Args);
+    let fn_ = parse_macro_input!(item as syn::ItemFn);
+    // NOTE: the handler is kept on errors, so that they are not followed by
+    // more about it being missing
+    syn::parse::<Args>(attr)
+        .and_then(|args| tapi_endpoint(args, fn_.clone()))
+        .unwrap_or_else(|err| {
+            let err = err.into_compile_error();
+            quote::quote!(#err #fn_)
+        })
+        .into()
+}
 
-    let fn_ = syn::parse2::<syn::ItemFn>(item.clone()).unwrap();
+fn tapi_endpoint(args: Args, fn_: syn::ItemFn) -> syn::Result<proc_macro2::TokenStream> {
+    let Args {
+        path,
+        methods,
+        name: client_name,
+        group,
+        state,
+        responses,
+        tracing,
+    } = args;
 
-    let name = fn_.sig.ident;
+    let name = fn_.sig.ident.clone();
     let mut body_ty = Vec::new();
     for inp in &fn_.sig.inputs {
         match inp {
-            syn::FnArg::Receiver(_) => {
-                todo!("idk what to do with receivers")
+            syn::FnArg::Receiver(receiver) => {
+                return Err(syn::Error::new_spanned(
+                    receiver,
+                    "`#[tapi]` handlers must be free functions, without `self`",
+                ));
             }
             syn::FnArg::Typed(t) => {
                 body_ty.push((*t.ty).clone());
@@ -85,40 +239,173 @@ pub fn tapi(
         }
     }
     let res_ty = match &fn_.sig.output {
-        syn::ReturnType::Default => None,
-        syn::ReturnType::Type(_, ty) => Some((**ty).clone()),
+        syn::ReturnType::Default => syn::parse_quote!(()),
+        syn::ReturnType::Type(_, ty) => (**ty).clone(),
     };
 
-    let res_ty = res_ty.unwrap_or_else(|| {
-        syn::parse2::<syn::Type>(quote::quote! {
-            ()
-        })
-        .unwrap()
-    });
+    // NOTE: handlers without a `State` extractor work with any router, so the
+    // endpoint is implemented for all of them
+    let state = state
+        .map(|state| quote::quote!(#state))
+        .or_else(|| body_ty.iter().find_map(state_ty).map(|s| quote::quote!(#s)));
+    let (impl_generics, state) = match state {
+        Some(state) => (quote::quote!(), state),
+        None => (
+            quote::quote!(<S: ::core::clone::Clone + ::core::marker::Send + ::core::marker::Sync + 'static>),
+            quote::quote!(S),
+        ),
+    };
+
+    let res_fns = match &res_ty {
+        // NOTE: `impl IntoResponse` cannot be named, so the declared responses
+        // are all there is to go by
+        syn::Type::ImplTrait(_) => {
+            let ok = responses
+                .iter()
+                .find(|r| {
+                    r.status
+                        .base10_parse::<u16>()
+                        .is_ok_and(|s| (200..300).contains(&s))
+                })
+                .or(responses.first())
+                .map(|r| &r.ty)
+                .ok_or_else(|| {
+                    syn::Error::new_spanned(
+                        &res_ty,
+                        "`impl` return types cannot be described, declare them with `#[tapi(responses(200 = ...))]`",
+                    )
+                })?;
+            quote::quote! {
+                fn res(&self) -> ::tapi::endpoints::ResponseTapi {
+                    <#ok as ::tapi::endpoints::ResponseTapiExtractor>::extract_response()
+                }
+            }
+        }
+        _ => quote::quote! {
+            fn res(&self) -> ::tapi::endpoints::ResponseTapi {
+                <#res_ty as ::tapi::endpoints::ResponseTapiExtractor>::extract_response()
+            }
+            fn error(&self) -> Option<::tapi::DynTapi> {
+                use ::tapi::endpoints::{TypedError as _, UntypedError as _};
+                (&&::tapi::endpoints::ErrorOf::<#res_ty>::new()).extract_error()
+            }
+        },
+    };
+    let responses_fn = if responses.is_empty() {
+        quote::quote!()
+    } else {
+        let status = responses.iter().map(|r| &r.status);
+        let ty = responses.iter().map(|r| &r.ty);
+        quote::quote! {
+            fn responses(&self) -> Vec<(u16, ::tapi::endpoints::ResponseTapi)> {
+                vec![#((
+                    #status,
+                    <#ty as ::tapi::endpoints::ResponseTapiExtractor>::extract_response(),
+                )),*]
+            }
+        }
+    };
 
-    let handler = match method.to_string().as_str() {
-        "Get" => format_ident!("get"),
-        "Post" => format_ident!("post"),
-        "Put" => format_ident!("put"),
-        "Delete" => format_ident!("delete"),
-        "Patch" => format_ident!("patch"),
-        _ => todo!("unknown method: {}", method.to_string()),
+    let name_fn = |method: &Ident| match &client_name {
+        Some(client_name) if methods.len() > 1 => {
+            let client_name = heck::AsLowerCamelCase(format!("{method} {client_name}")).to_string();
+            quote::quote! {
+                fn name(&self) -> String {
+                    #client_name.to_string()
+                }
+            }
+        }
+        Some(client_name) => quote::quote! {
+            fn name(&self) -> String {
+                #client_name.to_string()
+            }
+        },
+        None => quote::quote!(),
+    };
+    let group_fn = match group {
+        Some(group) => quote::quote! {
+            fn group(&self) -> Option<&'static str> {
+                Some(#group)
+            }
+        },
+        None => quote::quote!(),
     };
 
-    let output = quote::quote! {
-        mod #name {
-            #![allow(unused_parens)]
+    let method_names = methods
+        .iter()
+        .map(|m| m.to_string())
+        .collect::<Vec<_>>()
+        .join(" | ");
 
-            use super::*;
-            pub struct endpoint;
-            impl ::tapi::endpoints::Endpoint<AppState> for endpoint {
+    // NOTE: `tracing::instrument` expands to paths relative to `::tracing`,
+    // which would require a dependency on it, so the span is entered through
+    // the re-export instead
+    let item = match tracing {
+        Some(_) if fn_.sig.asyncness.is_none() => {
+            return Err(syn::Error::new_spanned(
+                fn_.sig.fn_token,
+                "`#[tapi(tracing)]` handlers must be `async fn`, as their body is instrumented as a future",
+            ));
+        }
+        Some(TracingArg { name, fields }) => {
+            let span_name = name.unwrap_or_else(|| "route".to_string());
+            let fields = if fields.is_empty() {
+                fields
+            } else {
+                quote::quote!(, #fields)
+            };
+            let syn::ItemFn {
+                attrs,
+                vis,
+                sig,
+                block,
+            } = fn_;
+            quote::quote! {
+                #(#attrs)*
+                #vis #sig {
+                    let span = ::tapi::tracing::info_span!(
+                        #span_name,
+                        path = #path,
+                        method = #method_names
+                        #fields
+                    );
+                    ::tapi::tracing::Instrument::instrument(async move #block, span).await
+                }
+            }
+        }
+        None => quote::quote!(#fn_),
+    };
+
+    let endpoints = methods.iter().map(|method| {
+        let handler = match method.to_string().as_str() {
+            "Get" => format_ident!("get"),
+            "Post" => format_ident!("post"),
+            "Put" => format_ident!("put"),
+            "Delete" => format_ident!("delete"),
+            "Patch" => format_ident!("patch"),
+            "Head" => format_ident!("head"),
+            "Options" => format_ident!("options"),
+            "Trace" => format_ident!("trace"),
+            _ => unreachable!("methods are checked when parsing"),
+        };
+        let endpoint = if methods.len() > 1 {
+            handler.clone()
+        } else {
+            format_ident!("endpoint")
+        };
+        let name_fn = name_fn(method);
+        quote::quote! {
+            pub struct #endpoint;
+            impl #impl_generics ::tapi::endpoints::Endpoint<#state> for #endpoint {
                 fn path(&self) -> &'static str {
                     #path
                 }
                 fn method(&self) -> ::tapi::endpoints::Method {
                     ::tapi::endpoints::Method::#method
                 }
-                fn bind_to(&self, router: ::axum::Router<AppState>) -> ::axum::Router<AppState> {
+                #name_fn
+                #group_fn
+                fn bind_to(&self, router: ::axum::Router<#state>) -> ::axum::Router<#state> {
                     router.route(#path, ::axum::routing::#handler(super::#name))
                 }
                 fn body(&self) -> ::tapi::endpoints::RequestStructure {
@@ -130,53 +417,181 @@ pub fn tapi(
                     )*
                     s
                 }
-                fn res(&self) -> ::tapi::endpoints::ResponseTapi {
-                    <#res_ty as ::tapi::endpoints::ResponseTapiExtractor>::extract_response()
-                }
+                #res_fns
+                #responses_fn
             }
         }
+    });
+    let endpoints_fn = if methods.len() > 1 {
+        let endpoint = methods
+            .iter()
+            .map(|m| format_ident!("{}", m.to_string().to_lowercase()));
+        quote::quote! {
+            pub fn endpoints #impl_generics () -> Vec<&'static dyn ::tapi::endpoints::Endpoint<#state>> {
+                vec![#(&#endpoint),*]
+            }
+        }
+    } else {
+        quote::quote!()
+    };
+
+    Ok(quote::quote! {
+        mod #name {
+            #![allow(unused_parens, non_camel_case_types)]
+
+            use super::*;
+            #(#endpoints)*
+            #endpoints_fn
+        }
 
-        #[tracing::instrument(name = "route", skip_all, fields(path = #path, method = stringify!(#method)))]
         #item
+    })
+}
+
+/// The `S` of a `State<S>` extractor.
+fn state_ty(ty: &syn::Type) -> Option<&syn::Type> {
+    let syn::Type::Path(ty) = ty else {
+        return None;
     };
-    output.into()
+    let last = ty.path.segments.last()?;
+    if last.ident != "State" {
+        return None;
+    }
+    match &last.arguments {
+        syn::PathArguments::AngleBracketed(args) => match args.args.first()? {
+            syn::GenericArgument::Type(ty) => Some(ty),
+            _ => None,
+        },
+        _ => None,
+    }
 }
 
 #[derive(Debug, Default, FromMeta)]
 struct DeriveInput {
     krate: Option<String>,
     path: Option<String>,
+    /// Replaces the `T: 'static + Tapi` bounds of the generated impl.
+    bound: Option<String>,
+    /// The name the type is declared as.
+    name: Option<String>,
 }
 
+/// Replaces the lifetime parameters of a type with `'static`.
+struct StaticLifetimes(Vec<Ident>);
+
+impl syn::visit_mut::VisitMut for StaticLifetimes {
+    fn visit_lifetime_mut(&mut self, lifetime: &mut syn::Lifetime) {
+        if self.0.contains(&lifetime.ident) {
+            *lifetime = syn::parse_quote!('static);
+        }
+    }
+}
+
+impl StaticLifetimes {
+    fn ty(&mut self, ty: &syn::Type) -> syn::Type {
+        let mut ty = ty.clone();
+        syn::visit_mut::VisitMut::visit_type_mut(self, &mut ty);
+        ty
+    }
+}
+
+/// Maps the type of a field to how the generic declaration refers to it.
+struct StandIns {
+    params: Vec<Ident>,
+    tapi_path: proc_macro2::TokenStream,
+}
+
+impl StandIns {
+    fn ty(&self, ty: &syn::Type) -> syn::Type {
+        if !self.mentions(ty) {
+            return ty.clone();
+        }
+        match ty {
+            syn::Type::Path(syn::TypePath { qself: None, path }) => {
+                if let Some(ident) = path.get_ident() {
+                    let param = format_ident!("__tapi_{}", ident);
+                    return syn::parse_quote!(#param);
+                }
+                let args = match &path.segments.last().unwrap().arguments {
+                    syn::PathArguments::AngleBracketed(args) => args
+                        .args
+                        .iter()
+                        .filter_map(|arg| match arg {
+                            syn::GenericArgument::Type(ty) => Some(self.ty(ty)),
+                            _ => None,
+                        })
+                        .collect::<Vec<_>>(),
+                    _ => Vec::new(),
+                };
+                if args.is_empty() {
+                    return ty.clone();
+                }
+                let tapi_path = &self.tapi_path;
+                syn::parse_quote!(#tapi_path::Applied<#ty, (#(#args,)*)>)
+            }
+            syn::Type::Tuple(tuple) => {
+                let elems = tuple.elems.iter().map(|ty| self.ty(ty));
+                syn::parse_quote!((#(#elems,)*))
+            }
+            syn::Type::Array(array) => {
+                let elem = self.ty(&array.elem);
+                let len = &array.len;
+                syn::parse_quote!([#elem; #len])
+            }
+            syn::Type::Paren(paren) => self.ty(&paren.elem),
+            syn::Type::Group(group) => self.ty(&group.elem),
+            _ => ty.clone(),
+        }
+    }
+
+    /// Whether any of the type parameters occurs in `ty`.
+    fn mentions(&self, ty: &syn::Type) -> bool {
+        struct Mentions<'a>(&'a [Ident], bool);
+        impl syn::visit_mut::VisitMut for Mentions<'_> {
+            fn visit_type_path_mut(&mut self, ty: &mut syn::TypePath) {
+                if ty.qself.is_none() && ty.path.get_ident().is_some_and(|i| self.0.contains(i)) {
+                    self.1 = true;
+                }
+                syn::visit_mut::visit_type_path_mut(self, ty);
+            }
+        }
+        let mut mentions = Mentions(&self.params, false);
+        syn::visit_mut::VisitMut::visit_type_mut(&mut mentions, &mut ty.clone());
+        mentions.1
+    }
+}
+
+/// Implements `tapi::Tapi` following the `#[serde(...)]` attributes.
 #[proc_macro_derive(Tapi, attributes(serde, tapi))]
 pub fn tapi_derive(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
-    let input = proc_macro2::TokenStream::from(input);
-
-    let derive_input = syn::parse2::<syn::DeriveInput>(input.clone()).unwrap();
+    let derive_input = parse_macro_input!(input as syn::DeriveInput);
+    derive(derive_input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
 
-    let tapi_derive_input = derive_input
+fn derive(derive_input: syn::DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let tapi_attr = derive_input
         .attrs
         .iter()
-        .find_map(|attr| {
-            if attr.meta.path().is_ident("tapi") {
-                Some(
-                    DeriveInput::from_meta(&attr.meta)
-                        .unwrap_or_else(|_| panic!("at: {}", line!())),
+        .find(|attr| attr.meta.path().is_ident("tapi"));
+    let tapi_derive_input = match tapi_attr {
+        Some(attr) => DeriveInput::from_meta(&attr.meta)?,
+        None => DeriveInput::default(),
+    };
+
+    let tapi_path = match &tapi_derive_input.krate {
+        Some(krate) => {
+            let krate = syn::parse_str::<syn::Path>(krate).map_err(|_| {
+                syn::Error::new_spanned(
+                    tapi_attr,
+                    format!("`krate = {krate:?}` is not a path, as in `krate = \"crate\"`"),
                 )
-            } else {
-                None
-            }
-        })
-        .unwrap_or_default();
-
-    let tapi_path = tapi_derive_input
-        .krate
-        .as_ref()
-        .map(|krate| {
-            syn::parse_str(krate)
-                .unwrap_or_else(|_| panic!("failed to parse krate path: {}", line!()))
-        })
-        .unwrap_or_else(|| quote::quote!(::tapi));
+            })?;
+            quote::quote!(#krate)
+        }
+        None => quote::quote!(::tapi),
+    };
 
     let path = match &tapi_derive_input.path {
         Some(path) => {
@@ -191,55 +606,126 @@ pub fn tapi_derive(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
     };
 
     let name = derive_input.ident.clone();
-    let generics = derive_input.generics.params.clone();
+    let mut lifetimes = StaticLifetimes(
+        derive_input
+            .generics
+            .lifetimes()
+            .map(|l| l.lifetime.ident.clone())
+            .collect(),
+    );
     let mut sgenerics = Vec::new();
-    let mut life_times = Vec::new();
-    for g in &generics {
+    let mut ty_args = Vec::new();
+    for g in &derive_input.generics.params {
         match g {
-            syn::GenericParam::Lifetime(l) => {
-                life_times.push(l.lifetime.clone());
-            }
+            syn::GenericParam::Lifetime(_) => ty_args.push(quote::quote!('static)),
             syn::GenericParam::Type(ty) => {
                 let ident = &ty.ident;
-                sgenerics.push(quote::quote!(#ident))
+                sgenerics.push(ident.clone());
+                ty_args.push(quote::quote!(#ident));
+            }
+            syn::GenericParam::Const(param) => {
+                let ident = &param.ident;
+                ty_args.push(quote::quote!(#ident));
             }
-            syn::GenericParam::Const(_) => todo!("syn::GenericParam::Const"),
         }
     }
+    let params = sgenerics
+        .iter()
+        .map(|g| format_ident!("__tapi_{}", g))
+        .collect::<Vec<_>>();
+
+    // NOTE: the impl is for the type with `'static` lifetimes, so they are
+    // only kept in its bounds
+    let mut generics = derive_input.generics.clone();
+    generics.params = generics
+        .params
+        .into_iter()
+        .filter(|g| !matches!(g, syn::GenericParam::Lifetime(_)))
+        .collect();
+    let bounds = match &tapi_derive_input.bound {
+        Some(bound) => syn::parse::Parser::parse_str(
+            syn::punctuated::Punctuated::<syn::WherePredicate, syn::Token![,]>::parse_terminated,
+            bound,
+        )
+        .map_err(|err| {
+            syn::Error::new_spanned(
+                tapi_attr,
+                format!("`bound = {bound:?}` is not a list of where predicates: {err}"),
+            )
+        })?,
+        None => sgenerics
+            .iter()
+            .map(|g| -> syn::WherePredicate { syn::parse_quote!(#g: 'static + #tapi_path::Tapi) })
+            .collect(),
+    };
+    generics.make_where_clause().predicates.extend(bounds);
+    syn::visit_mut::VisitMut::visit_generics_mut(&mut lifetimes, &mut generics);
+    let (impl_generics, _, where_clause) = generics.split_for_impl();
+    let impl_header = quote::quote!(
+        impl #impl_generics #tapi_path::Tapi for #name<#(#ty_args),*> #where_clause
+    );
+
+    let generic_fns = if sgenerics.is_empty() {
+        quote::quote!()
+    } else {
+        quote::quote!(
+            fn generics() -> Vec<#tapi_path::DynTapi> {
+                vec![#(<#sgenerics as #tapi_path::Tapi>::boxed()),*]
+            }
+        )
+    };
+    let is_generic = !params.is_empty() || generics.const_params().next().is_some();
+    let generic_fns = if is_generic {
+        quote::quote!(
+            #generic_fns
+            fn generic_decl() -> #tapi_path::DynTapi {
+                <__tapi_Decl<Self> as #tapi_path::Tapi>::boxed()
+            }
+        )
+    } else {
+        generic_fns
+    };
     let container = {
         let cx = serde_derive_internals::Ctxt::new();
         let container = ast::Container::from_ast(
             &cx,
             &derive_input,
             serde_derive_internals::Derive::Serialize,
-        )
-        .unwrap();
-        cx.check().unwrap();
-        container
+        );
+        cx.check()?;
+        container.ok_or_else(|| {
+            syn::Error::new_spanned(
+                &derive_input.ident,
+                "`Tapi` cannot be derived for this type",
+            )
+        })?
     };
 
-    let attr = build_container_attributes(&container, &tapi_path);
+    let attr =
+        build_container_attributes(&container, tapi_derive_input.name.as_deref(), &tapi_path);
+    let docs = build_docs(&derive_input.attrs);
 
-    let result: proc_macro2::TokenStream = match &container.data {
+    // NOTE: the kind is built once for the type itself, and once for its
+    // generic declaration, with `ty` mapping the type of each field
+    let kind = |ty: &mut dyn FnMut(&syn::Type) -> syn::Type| match &container.data {
         ast::Data::Struct(_style, st_fields) => {
             // TODO: rewrite this to use the `style`
-            let mut fields = Vec::new();
             let mut kind_fields = Vec::new();
             let mut tuple_fields = Vec::new();
             for field in st_fields {
-                let ty = field.ty.clone();
+                let ty = ty(field.ty);
                 let field_flags = &field;
                 let attr = build_field_attributes(&field_flags.attrs, &tapi_path);
+                let docs = build_docs(&field.original.attrs);
                 let field_name = match field.original.ident.clone() {
                     Some(_) => {
-                        let serialize_name =
-                            format_ident!("{}", field.attrs.name().serialize_name());
-                        let deserialize_name =
-                            format_ident!("{}", field.attrs.name().deserialize_name());
+                        // NOTE: renamed fields need not be identifiers, e.g. `kebab-case`
+                        let serialize_name = field.attrs.name().serialize_name();
+                        let deserialize_name = field.attrs.name().deserialize_name();
 
                         quote::quote!(#tapi_path::kind::FieldName::Named(#tapi_path::kind::Name {
-                            serialize_name: stringify!(#serialize_name).to_string(),
-                            deserialize_name: stringify!(#deserialize_name).to_string(),
+                            serialize_name: #serialize_name.to_string(),
+                            deserialize_name: #deserialize_name.to_string(),
                         }))
                     }
                     None => {
@@ -250,53 +736,31 @@ pub fn tapi_derive(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
                         continue;
                     }
                 };
-                fields.push(field.ty.clone());
                 kind_fields.push(quote::quote!(
                     #tapi_path::kind::Field {
                         attr: #attr,
                         name: #field_name,
                         ty: <#ty as #tapi_path::Tapi>::boxed(),
+                        docs: #docs,
                     }
                 ));
             }
             if tuple_fields.is_empty() {
                 quote::quote! {
-                    #[allow(unused_parens)]
-                    impl<#(#life_times,)* #(#sgenerics: 'static + #tapi_path::Tapi),*> #tapi_path::Tapi for #name<#(#life_times,)* #(#sgenerics),*> {
-                        fn name() -> &'static str {
-                            stringify!(#name)
-                        }
-                        fn id() -> std::any::TypeId {
-                            std::any::TypeId::of::<#name<#(#sgenerics),*>>()
-                        }
-                        #path
-                        fn kind() -> #tapi_path::kind::TypeKind {
-                            #tapi_path::kind::TypeKind::Struct(#tapi_path::kind::Struct {
-                                attr: #attr,
-                                fields: [#(#kind_fields),*].to_vec(),
-                            })
-                        }
-                    }
+                    #tapi_path::kind::TypeKind::Struct(#tapi_path::kind::Struct {
+                        attr: #attr,
+                        fields: [#(#kind_fields),*].to_vec(),
+                        docs: #docs,
+                    })
                 }
             } else {
                 assert!(kind_fields.is_empty());
                 quote::quote! {
-                    #[allow(unused_parens)]
-                    impl<#(#life_times,)* #(#sgenerics: 'static + #tapi_path::Tapi),*> #tapi_path::Tapi for #name<#(#life_times,)* #(#sgenerics),*> {
-                        fn name() -> &'static str {
-                            stringify!(#name)
-                        }
-                        fn id() -> std::any::TypeId {
-                            std::any::TypeId::of::<#name<#(#sgenerics),*>>()
-                        }
-                        #path
-                        fn kind() -> #tapi_path::kind::TypeKind {
-                            #tapi_path::kind::TypeKind::TupleStruct(#tapi_path::kind::TupleStruct {
-                                attr: #attr,
-                                fields: [#(#tuple_fields),*].to_vec(),
-                            })
-                        }
-                    }
+                    #tapi_path::kind::TypeKind::TupleStruct(#tapi_path::kind::TupleStruct {
+                        attr: #attr,
+                        fields: [#(#tuple_fields),*].to_vec(),
+                        docs: #docs,
+                    })
                 }
             }
         }
@@ -304,6 +768,7 @@ pub fn tapi_derive(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
             let mut kind_variants = Vec::new();
             for variant in en_variants {
                 let ident = &variant.ident;
+                let variant_docs = build_docs(&variant.original.attrs);
 
                 match &variant.style {
                     ast::Style::Unit => {
@@ -311,86 +776,182 @@ pub fn tapi_derive(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
 
                         kind_variants.push(quote::quote!(#tapi_path::kind::EnumVariant {
                             name: stringify!(#ident).to_string(),
+                            docs: #variant_docs,
                             kind: #tapi_path::kind::VariantKind::Unit,
                         }))
                     }
                     ast::Style::Struct => {
                         let fields = variant.fields.iter().map(|f| {
-                            let ty = f.ty.clone();
+                            let ty = ty(f.ty);
                             let attr = build_field_attributes(&f.attrs, &tapi_path);
+                            let docs = build_docs(&f.original.attrs);
 
-                            let serialize_name =
-                                format_ident!("{}", f.attrs.name().serialize_name());
-                            let deserialize_name =
-                                format_ident!("{}", f.attrs.name().deserialize_name());
+                            let serialize_name = f.attrs.name().serialize_name();
+                            let deserialize_name = f.attrs.name().deserialize_name();
 
                             quote::quote!(
                                 #tapi_path::kind::Field {
                                     attr: #attr,
                                     name: #tapi_path::kind::FieldName::Named(#tapi_path::kind::Name {
-                                        serialize_name: stringify!(#serialize_name).to_string(),
-                                        deserialize_name: stringify!(#deserialize_name).to_string(),
+                                        serialize_name: #serialize_name.to_string(),
+                                        deserialize_name: #deserialize_name.to_string(),
                                     }),
                                     ty: <#ty as #tapi_path::Tapi>::boxed(),
+                                    docs: #docs,
                                 }
                             )
-                        });
+                        }).collect::<Vec<_>>();
                         kind_variants.push(quote::quote!(#tapi_path::kind::EnumVariant {
                             name: stringify!(#ident).to_string(),
+                            docs: #variant_docs,
                             kind: #tapi_path::kind::VariantKind::Struct([#(#fields),*].to_vec()),
                         }))
                     }
                     ast::Style::Tuple => {
-                        let fields = variant.fields.iter().map(|f| f.ty.clone());
+                        let fields = variant.fields.iter().map(|f| ty(f.ty)).collect::<Vec<_>>();
                         kind_variants.push(quote::quote!(#tapi_path::kind::EnumVariant {
                             name: stringify!(#ident).to_string(),
+                            docs: #variant_docs,
                             kind: #tapi_path::kind::VariantKind::Tuple([#(<#fields as #tapi_path::Tapi>::boxed()),*].to_vec()),
                         }))
                     }
                     ast::Style::Newtype => {
                         assert_eq!(variant.fields.len(), 1, "newtype has exactly one field");
 
-                        let fields = variant.fields.iter().map(|f| f.ty.clone());
+                        let fields = variant.fields.iter().map(|f| ty(f.ty)).collect::<Vec<_>>();
                         kind_variants.push(quote::quote!(#tapi_path::kind::EnumVariant {
                             name: stringify!(#ident).to_string(),
+                            docs: #variant_docs,
                             kind: #tapi_path::kind::VariantKind::Tuple([#(<#fields as #tapi_path::Tapi>::boxed()),*].to_vec()),
                         }))
                     }
                 }
             }
             quote::quote! {
+                #tapi_path::kind::TypeKind::Enum(#tapi_path::kind::Enum {
+                    attr: #attr,
+                    variants: [#(#kind_variants),*].to_vec(),
+                    docs: #docs,
+                })
+            }
+        }
+    };
+
+    let ty_kind = kind(&mut |ty| lifetimes.ty(ty));
+    let result = quote::quote! {
+        #[allow(unused_parens)]
+        #impl_header {
+            fn name() -> &'static str {
+                stringify!(#name)
+            }
+            fn id() -> std::any::TypeId {
+                std::any::TypeId::of::<Self>()
+            }
+            #path
+            #generic_fns
+            fn kind() -> #tapi_path::kind::TypeKind {
+                #ty_kind
+            }
+        }
+    };
+
+    // NOTE: generic types are declared once, by a marker type shared by all
+    // instantiations, whose fields have a stand-in in place of each type
+    // parameter. Neither the type itself nor the generic types of its fields
+    // are instantiated with the stand-ins, which need not satisfy their bounds
+    let result = if is_generic {
+        let stand_ins = StandIns {
+            params: sgenerics.clone(),
+            tapi_path: tapi_path.clone(),
+        };
+        let decl_kind = kind(&mut |ty| stand_ins.ty(&lifetimes.ty(ty)));
+        let param_names = sgenerics.iter().map(|g| g.to_string()).collect::<Vec<_>>();
+        quote::quote! {
+            const _: () = {
+                #(
+                    #[allow(non_camel_case_types)]
+                    struct #params;
+                    impl #tapi_path::Tapi for #params {
+                        fn name() -> &'static str {
+                            #param_names
+                        }
+                        fn kind() -> #tapi_path::kind::TypeKind {
+                            #tapi_path::kind::TypeKind::Param(#param_names.to_string())
+                        }
+                        fn path() -> Vec<&'static str> {
+                            Vec::new()
+                        }
+                    }
+                )*
+                #[allow(non_camel_case_types)]
+                struct __tapi_Decl<T>(::core::marker::PhantomData<T>);
                 #[allow(unused_parens)]
-                impl<#(#life_times,)* #(#sgenerics: 'static + #tapi_path::Tapi),*> #tapi_path::Tapi for #name<#(#life_times,)* #(#sgenerics),*> {
+                impl #impl_generics #tapi_path::Tapi for __tapi_Decl<#name<#(#ty_args),*>> #where_clause {
                     fn name() -> &'static str {
                         stringify!(#name)
                     }
                     fn id() -> std::any::TypeId {
-                        std::any::TypeId::of::<#name>()
+                        std::any::TypeId::of::<__tapi_Decl<()>>()
+                    }
+                    fn path() -> Vec<&'static str> {
+                        <#name<#(#ty_args),*> as #tapi_path::Tapi>::path()
+                    }
+                    fn generics() -> Vec<#tapi_path::DynTapi> {
+                        vec![#(<#params as #tapi_path::Tapi>::boxed()),*]
                     }
-                    #path
                     fn kind() -> #tapi_path::kind::TypeKind {
-                        #tapi_path::kind::TypeKind::Enum(#tapi_path::kind::Enum {
-                            attr: #attr,
-                            variants: [#(#kind_variants),*].to_vec(),
-                        })
+                        #decl_kind
                     }
                 }
-            }
+                #result
+            };
         }
+    } else {
+        result
     };
 
     // let pretty = prettyplease::unparse(&syn::parse2(result.clone()).unwrap());
     // eprintln!("{pretty}");
-    result.into()
+    Ok(result)
+}
+
+/// The `///` doc comment in `attrs`, as an `Option<String>` expression, with
+/// the leading space of each line removed.
+fn build_docs(attrs: &[syn::Attribute]) -> proc_macro2::TokenStream {
+    let lines = attrs
+        .iter()
+        .filter_map(|attr| match &attr.meta {
+            syn::Meta::NameValue(syn::MetaNameValue {
+                path,
+                value:
+                    syn::Expr::Lit(syn::ExprLit {
+                        lit: syn::Lit::Str(doc),
+                        ..
+                    }),
+                ..
+            }) if path.is_ident("doc") => Some(doc.value()),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    if lines.is_empty() {
+        return quote::quote!(None);
+    }
+    let docs = lines
+        .iter()
+        .map(|l| l.strip_prefix(' ').unwrap_or(l))
+        .collect::<Vec<_>>()
+        .join("\n");
+    quote::quote!(Some(#docs.to_string()))
 }
 
 fn build_container_attributes(
     serde_flags: &ast::Container<'_>,
+    rename: Option<&str>,
     tapi_path: &proc_macro2::TokenStream,
 ) -> proc_macro2::TokenStream {
     let name = {
-        let serialize_name = serde_flags.attrs.name().serialize_name();
-        let deserialize_name = serde_flags.attrs.name().deserialize_name();
+        let serialize_name = rename.unwrap_or(serde_flags.attrs.name().serialize_name());
+        let deserialize_name = rename.unwrap_or(serde_flags.attrs.name().deserialize_name());
         quote::quote!(#tapi_path::kind::Name {
             serialize_name: #serialize_name.to_string(),
             deserialize_name: #deserialize_name.to_string(),
@@ -496,10 +1057,23 @@ fn build_field_attributes(
     };
     let aliases = {
         let aliases = serde_flags.aliases();
-        quote::quote!([#(stringify!(#aliases).to_string()),*].into_iter().collect())
+        quote::quote!([#(#aliases.to_string()),*].into_iter().collect())
     };
     let skip_serializing = serde_flags.skip_serializing();
     let skip_deserializing = serde_flags.skip_deserializing();
+    let skip_serializing_if = match serde_flags.skip_serializing_if() {
+        Some(path) => {
+            let path = path
+                .path
+                .segments
+                .iter()
+                .map(|s| s.ident.to_string())
+                .collect::<Vec<_>>()
+                .join("::");
+            quote::quote!(Some(#path.to_string()))
+        }
+        None => quote::quote!(None),
+    };
     let default = match serde_flags.default() {
         serde_derive_internals::attr::Default::None => {
             quote::quote!(#tapi_path::kind::Default::None)
@@ -518,7 +1092,7 @@ fn build_field_attributes(
         aliases: #aliases,
         skip_serializing: #skip_serializing,
         skip_deserializing: #skip_deserializing,
-        // skip_serializing_if: #skip_serializing_if,
+        skip_serializing_if: #skip_serializing_if,
         default: #default,
         // serialize_with: #serialize_with,
         // deserialize_with: #deserialize_with,
@@ -1,16 +1,37 @@
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
 
-use crate::DynTapi;
+use crate::{kind::TypeKind, DynTapi, Error};
+
+pub type NamespaceFn = Box<dyn Fn(&[String], &str) -> String>;
 
 pub struct TypesBuilder {
     pub prelude: String,
-    pub start_namespace: Box<dyn Fn(&[String], &str) -> String>,
-    pub end_namespace: Box<dyn Fn(&[String], &str) -> String>,
-    pub decl: Box<dyn Fn(DynTapi) -> Option<String>>,
+    pub start_namespace: NamespaceFn,
+    pub end_namespace: NamespaceFn,
+    pub decl: Box<dyn Fn(DynTapi) -> Result<Option<String>, Error>>,
+}
+
+/// Two different types that would be declared under the same name.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NameCollision {
+    /// The path and name both are declared under, e.g. `api.Error`.
+    pub name: String,
+    /// The Rust types, in the order they were found.
+    pub tys: [&'static str; 2],
+}
+impl std::fmt::Display for NameCollision {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "`{}` and `{}` are both declared as `{}`, use `#[tapi(name = \"...\")]` to rename one of them",
+            self.tys[0], self.tys[1], self.name
+        )
+    }
 }
+impl std::error::Error for NameCollision {}
 
 impl TypesBuilder {
-    pub fn types(&self, tys: impl IntoIterator<Item = DynTapi>) -> String {
+    pub fn types(&self, tys: impl IntoIterator<Item = DynTapi>) -> Result<String, Error> {
         let mut s = self.prelude.trim_start().to_string();
 
         pub struct Node<'a> {
@@ -22,7 +43,34 @@ impl TypesBuilder {
 
         let mut root = Node::new(Vec::new(), self);
 
+        // NOTE: every instantiation of a generic type shares a single
+        // declaration
+        let mut seen = HashSet::new();
+        let mut names = HashMap::new();
         for ty in tys {
+            if !seen.insert(ty.generic_decl().id()) {
+                continue;
+            }
+            let name = match ty.kind() {
+                TypeKind::Struct(s) => Some(s.attr.name.serialize_name),
+                TypeKind::TupleStruct(s) => Some(s.attr.name.serialize_name),
+                TypeKind::Enum(e) => Some(e.attr.name.serialize_name),
+                _ => None,
+            };
+            if let Some(name) = name {
+                let name = ty
+                    .path()
+                    .into_iter()
+                    .chain([name.as_str()])
+                    .collect::<Vec<_>>();
+                if let Some(other) = names.insert(name.join("."), ty) {
+                    return Err(NameCollision {
+                        name: name.join("."),
+                        tys: [other.type_name(), ty.type_name()],
+                    }
+                    .into());
+                }
+            }
             let mut node = &mut root;
             let mut path = Vec::new();
             for p in ty.path() {
@@ -45,9 +93,9 @@ impl TypesBuilder {
                 }
             }
 
-            fn write(&self, s: &mut String, indent: usize) {
+            fn write(&self, s: &mut String, indent: usize) -> Result<(), Error> {
                 for decl in &self.decls {
-                    if let Some(decl) = (self.builder.decl)(*decl) {
+                    if let Some(decl) = (self.builder.decl)(*decl)? {
                         for l in decl.lines() {
                             for _ in 0..indent {
                                 s.push_str("  ");
@@ -63,18 +111,19 @@ impl TypesBuilder {
                     }
                     s.push_str(&(self.builder.start_namespace)(&node.path, name));
                     s.push('\n');
-                    node.write(s, indent + 1);
+                    node.write(s, indent + 1)?;
                     for _ in 0..indent {
                         s.push_str("  ");
                     }
                     s.push_str(&(self.builder.end_namespace)(&node.path, name));
                     s.push('\n');
                 }
+                Ok(())
             }
         }
 
-        root.write(&mut s, 0);
+        root.write(&mut s, 0)?;
 
-        s
+        Ok(s)
     }
 }
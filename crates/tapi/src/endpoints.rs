@@ -1,25 +1,37 @@
 use futures_util::StreamExt;
+use indexmap::IndexMap;
 use itertools::Itertools;
 
 use crate::{
-    targets::{js, ts},
-    transitive_closure, DynTapi, Tapi,
+    error,
+    kind::{FieldName, TypeKind, View},
+    targets::{js, openapi, ts},
+    transitive_closure, DynTapi, Error, Tapi,
 };
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Method {
     Get,
     Post,
     Put,
     Delete,
+    Patch,
+    Head,
+    Options,
+    Trace,
 }
 impl Method {
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(s: &str) -> Option<Self> {
         match s {
             "GET" => Some(Self::Get),
             "POST" => Some(Self::Post),
             "PUT" => Some(Self::Put),
             "DELETE" => Some(Self::Delete),
+            "PATCH" => Some(Self::Patch),
+            "HEAD" => Some(Self::Head),
+            "OPTIONS" => Some(Self::Options),
+            "TRACE" => Some(Self::Trace),
             _ => None,
         }
     }
@@ -29,6 +41,10 @@ impl Method {
             Self::Post => "POST",
             Self::Put => "PUT",
             Self::Delete => "DELETE",
+            Self::Patch => "PATCH",
+            Self::Head => "HEAD",
+            Self::Options => "OPTIONS",
+            Self::Trace => "TRACE",
         }
     }
 }
@@ -37,6 +53,13 @@ impl Method {
 pub enum RequestStructureBody {
     Query(DynTapi),
     Json(DynTapi),
+    // `application/x-www-form-urlencoded`
+    Form(DynTapi),
+    // `multipart/form-data`, with the fields described by the type if known
+    Multipart(Option<DynTapi>),
+    // `application/octet-stream`
+    Bytes,
+    // `text/plain; charset=utf-8`
     PlainText,
 }
 #[derive(Debug)]
@@ -44,6 +67,17 @@ pub struct RequestStructure {
     pub path: Option<DynTapi>,
     pub method: Method,
     pub body: Option<RequestStructureBody>,
+    /// The headers the endpoint reads.
+    pub headers: Vec<RequestParam>,
+    /// The cookies the endpoint reads.
+    pub cookies: Vec<RequestParam>,
+}
+/// A header or cookie parameter of a request.
+#[derive(Debug, Clone, Copy)]
+pub struct RequestParam {
+    pub name: &'static str,
+    pub ty: DynTapi,
+    pub required: bool,
 }
 
 impl RequestStructure {
@@ -52,6 +86,8 @@ impl RequestStructure {
             path: None,
             method,
             body: None,
+            headers: Vec::new(),
+            cookies: Vec::new(),
         }
     }
     pub fn merge_with(&mut self, req: RequestTapi) {
@@ -65,6 +101,24 @@ impl RequestStructure {
             RequestTapi::Json(ty) => {
                 self.body = Some(RequestStructureBody::Json(ty));
             }
+            RequestTapi::Form(ty) => {
+                self.body = Some(RequestStructureBody::Form(ty));
+            }
+            RequestTapi::Multipart(ty) => {
+                self.body = Some(RequestStructureBody::Multipart(ty));
+            }
+            RequestTapi::Bytes => {
+                self.body = Some(RequestStructureBody::Bytes);
+            }
+            RequestTapi::PlainText => {
+                self.body = Some(RequestStructureBody::PlainText);
+            }
+            RequestTapi::Header(param) => {
+                self.headers.push(param);
+            }
+            RequestTapi::Cookie(param) => {
+                self.cookies.push(param);
+            }
             RequestTapi::None => {}
         }
     }
@@ -72,9 +126,26 @@ impl RequestStructure {
 pub trait Endpoint<AppState> {
     fn path(&self) -> &'static str;
     fn method(&self) -> Method;
+    /// The key of this endpoint in the generated clients, e.g. `postUsers`.
+    fn name(&self) -> String {
+        let name = format!("{} {}", self.method().as_str().to_lowercase(), self.path());
+        heck::AsLowerCamelCase(name).to_string()
+    }
+    /// The group set with `#[tapi(group = "...")]`, e.g. `admin.users`.
+    fn group(&self) -> Option<&'static str> {
+        None
+    }
     fn bind_to(&self, router: axum::Router<AppState>) -> axum::Router<AppState>;
     fn body(&self) -> RequestStructure;
     fn res(&self) -> ResponseTapi;
+    /// The body of error responses of `Result<T, E>` handlers.
+    fn error(&self) -> Option<DynTapi> {
+        None
+    }
+    /// The responses declared with `#[tapi(responses(...))]`.
+    fn responses(&self) -> Vec<(u16, ResponseTapi)> {
+        Vec::new()
+    }
     fn tys(&self) -> Vec<DynTapi> {
         let mut tys = Vec::new();
         if let Some(path) = self.body().path {
@@ -82,16 +153,27 @@ pub trait Endpoint<AppState> {
         }
         if let Some(body) = self.body().body {
             match body {
-                RequestStructureBody::Query(ty) => {
-                    tys.push(ty);
-                }
-                RequestStructureBody::Json(ty) => {
+                RequestStructureBody::Query(ty)
+                | RequestStructureBody::Json(ty)
+                | RequestStructureBody::Form(ty)
+                | RequestStructureBody::Multipart(Some(ty)) => {
                     tys.push(ty);
                 }
-                RequestStructureBody::PlainText => {}
+                RequestStructureBody::Multipart(None)
+                | RequestStructureBody::Bytes
+                | RequestStructureBody::PlainText => {}
             }
         }
+        let body = self.body();
+        tys.extend(
+            body.headers
+                .iter()
+                .chain(&body.cookies)
+                .map(|param| param.ty),
+        );
         tys.push(self.res().ty());
+        tys.extend(self.error());
+        tys.extend(self.responses().into_iter().map(|(_, res)| res.ty()));
         tys
     }
     /// Generate a TypeScript client for this endpoint.
@@ -99,11 +181,11 @@ pub trait Endpoint<AppState> {
     /// The generated client will look something like this:
     /// ```ignore
     /// export const api = {
-    ///     index: request<{}, string>("none", "GET", "/", "text"),
-    ///     api: request<Person, string>("json", "GET", "/api", "json"),
-    ///     api2AB: request<{}, string>("none", "GET", "/api2/:a/:b", "text"),
-    ///     wow: sse<Msg>("/wow", "json"),
-    ///     cool: request<Record<string, string>, Msg>("json", "GET", "/cool", "json"),
+    ///     get: request<{}, string>("none", "GET", "/", "text"),
+    ///     getApi: request<Person, string>("json", "GET", "/api", "json"),
+    ///     getApi2AB: request<{}, string, { a: string, b: number }>("none", "GET", "/api2/:a/:b", "text"),
+    ///     getWow: sse<Msg>("/wow", "json"),
+    ///     postCool: request<Record<string, string>, Msg>("json", "POST", "/cool", "json"),
     /// };
     /// ```
     fn ts_client(&self) -> String {
@@ -135,7 +217,7 @@ pub trait Endpoint<AppState> {
                     write!(
                         s,
                         "sse<[{}], {}>(({}) => {final_path}, \"json\")",
-                        ts::full_ty_name(path_param),
+                        ts::full_view_name(path_param, View::Input),
                         ts::full_ty_name(ty),
                         params.iter().format(", "),
                     )
@@ -144,43 +226,39 @@ pub trait Endpoint<AppState> {
                     // TODO: handle non-json responses
                     write!(
                         s,
-                        "sse<[{}], {}>(({}) => {final_path}, \"json\")",
-                        "",
+                        "sse<[], {}>(({}) => {final_path}, \"json\")",
                         ts::full_ty_name(ty),
                         params.iter().format(", "),
                     )
                     .unwrap();
                 }
             }
-            (RequestStructure { body, .. }, res) => {
+            (req, _) if !self.responses().is_empty() => {
+                let (res, res_tys) = ts_responses(&self.responses());
                 write!(
                     s,
-                    "request<{}, {}>({:?}, {:?}, {:?}, {:?})",
-                    match body {
-                        Some(RequestStructureBody::Query(ty)) => ts::full_ty_name(ty),
-                        Some(RequestStructureBody::Json(ty)) => ts::full_ty_name(ty),
-                        // TODO: is this right?
-                        Some(RequestStructureBody::PlainText) =>
-                            "Record<string, never>".to_string(),
-                        None => "Record<string, never>".to_string(),
-                    },
+                    "requestStatus<{}, {res}{}>({:?}, {:?}, {:?}, {res_tys}{})",
+                    ts_req_ty(req.body),
+                    ts_extra_generics(self.path(), &req, None),
+                    req_ty(req.body),
+                    self.method().as_str(),
+                    self.path(),
+                    request_options(&req, None),
+                )
+                .unwrap();
+            }
+            (req, res) => {
+                write!(
+                    s,
+                    "request<{}, {}{}>({:?}, {:?}, {:?}, {:?}{})",
+                    ts_req_ty(req.body),
                     ts::full_ty_name(res.ty()),
-                    match body {
-                        Some(RequestStructureBody::Query(_)) => "query",
-                        Some(RequestStructureBody::Json(_)) => "json",
-                        Some(RequestStructureBody::PlainText) => "none",
-                        None => "none",
-                    },
+                    ts_extra_generics(self.path(), &req, self.error()),
+                    req_ty(req.body),
                     self.method().as_str(),
                     self.path(),
-                    match res {
-                        ResponseTapi::PlainText => "text",
-                        ResponseTapi::Bytes => "bytes",
-                        ResponseTapi::Json(_) => "json",
-                        ResponseTapi::Html => "html",
-                        ResponseTapi::Sse(_) => "sse",
-                        ResponseTapi::None => "none",
-                    }
+                    res_ty(res),
+                    request_options(&req, self.error()),
                 )
                 .unwrap();
             }
@@ -216,7 +294,7 @@ pub trait Endpoint<AppState> {
                     write!(
                         s,
                         "/** @type {{ReturnType<typeof sse<[{}], {}>>}} */ (\n    sse(({}) => {final_path}, \"json\")\n  )",
-                        ts::full_ty_name(path_param),
+                        ts::full_view_name(path_param, View::Input),
                         ts::full_ty_name(ty),
                         params.iter().format(", "),
                     )
@@ -225,51 +303,365 @@ pub trait Endpoint<AppState> {
                     // TODO: handle non-json responses
                     write!(
                         s,
-                        "/** @type {{ReturnType<typeof sse<[{}], {}>>}} */ (\n    sse(({}) => {final_path}, \"json\")\n  )",
-                        "",
+                        "/** @type {{ReturnType<typeof sse<[], {}>>}} */ (\n    sse(({}) => {final_path}, \"json\")\n  )",
                         ts::full_ty_name(ty),
                         params.iter().format(", "),
                     )
                     .unwrap();
                 }
             }
-            (RequestStructure { body, .. }, res) => {
+            (req, _) if !self.responses().is_empty() => {
+                let (res, res_tys) = ts_responses(&self.responses());
                 write!(
                     s,
-                    "/** @type {{ReturnType<typeof request<{}, {}>>}} */ (\n    request({:?}, {:?}, {:?}, {:?})\n  )",
-                    match body {
-                        Some(RequestStructureBody::Query(ty)) => ts::full_ty_name(ty),
-                        Some(RequestStructureBody::Json(ty)) => ts::full_ty_name(ty),
-                        // TODO: is this right?
-                        Some(RequestStructureBody::PlainText) =>
-                            "Record<string, never>".to_string(),
-                        None => "Record<string, never>".to_string(),
-                    },
+                    "/** @type {{ReturnType<typeof requestStatus<{}, {res}{}>>}} */ (\n    requestStatus({:?}, {:?}, {:?}, {res_tys}{})\n  )",
+                    ts_req_ty(req.body),
+                    ts_extra_generics(self.path(), &req, None),
+                    req_ty(req.body),
+                    self.method().as_str(),
+                    self.path(),
+                    request_options(&req, None),
+                )
+                .unwrap();
+            }
+            (req, res) => {
+                write!(
+                    s,
+                    "/** @type {{ReturnType<typeof request<{}, {}{}>>}} */ (\n    request({:?}, {:?}, {:?}, {:?}{})\n  )",
+                    ts_req_ty(req.body),
                     ts::full_ty_name(res.ty()),
-                    match body {
-                        Some(RequestStructureBody::Query(_)) => "query",
-                        Some(RequestStructureBody::Json(_)) => "json",
-                        Some(RequestStructureBody::PlainText) => "none",
-                        None => "none",
-                    },
+                    ts_extra_generics(self.path(), &req, self.error()),
+                    req_ty(req.body),
                     self.method().as_str(),
                     self.path(),
-                    match res {
-                        ResponseTapi::PlainText => "text",
-                        ResponseTapi::Bytes => "bytes",
-                        ResponseTapi::Json(_) => "json",
-                        ResponseTapi::Html => "html",
-                        ResponseTapi::Sse(_) => "sse",
-                        ResponseTapi::None => "none",
-                    }
+                    res_ty(res),
+                    request_options(&req, self.error()),
                 )
                 .unwrap();
             }
         }
         s
     }
+    /// The path in OpenAPI notation, i.e. `/api2/:a/:b` becomes `/api2/{a}/{b}`.
+    fn openapi_path(&self) -> String {
+        let path = self
+            .path()
+            .split('/')
+            .filter(|p| !p.is_empty())
+            .map(
+                |p| match p.strip_prefix(':').or_else(|| p.strip_prefix('*')) {
+                    Some(name) => format!("/{{{name}}}"),
+                    None => format!("/{p}"),
+                },
+            )
+            .join("");
+        if path.is_empty() {
+            "/".to_string()
+        } else {
+            path
+        }
+    }
+    /// Generate the OpenAPI operation object for this endpoint.
+    fn openapi_operation(&self) -> serde_json::Value {
+        use serde_json::json;
+
+        let body = self.body();
+        let mut parameters = Vec::new();
+
+        for (name, ty) in path_params(self.path(), body.path) {
+            parameters.push(json!({
+                "name": name,
+                "in": "path",
+                "required": true,
+                "schema": ty.map_or_else(|| json!({ "type": "string" }), openapi::ty_schema),
+            }));
+        }
+
+        let mut request_body = None;
+        match body.body {
+            Some(RequestStructureBody::Query(ty)) => parameters.extend(query_params(ty, "query")),
+            Some(RequestStructureBody::Json(ty)) => {
+                request_body = Some(json!({
+                    "required": true,
+                    "content": { "application/json": { "schema": openapi::ty_schema(ty) } },
+                }));
+            }
+            Some(RequestStructureBody::Form(ty)) => {
+                request_body = Some(json!({
+                    "required": true,
+                    "content": {
+                        "application/x-www-form-urlencoded": { "schema": openapi::ty_schema(ty) },
+                    },
+                }));
+            }
+            Some(RequestStructureBody::Multipart(ty)) => {
+                let schema = ty.map_or_else(|| json!({ "type": "object" }), openapi::ty_schema);
+                request_body = Some(json!({
+                    "required": true,
+                    "content": { "multipart/form-data": { "schema": schema } },
+                }));
+            }
+            Some(RequestStructureBody::Bytes) => {
+                request_body = Some(json!({
+                    "required": true,
+                    "content": {
+                        "application/octet-stream": {
+                            "schema": { "type": "string", "contentEncoding": "binary" },
+                        },
+                    },
+                }));
+            }
+            Some(RequestStructureBody::PlainText) => {
+                request_body = Some(json!({
+                    "required": true,
+                    "content": { "text/plain": { "schema": { "type": "string" } } },
+                }));
+            }
+            None => {}
+        }
+        for (params, location) in [(&body.headers, "header"), (&body.cookies, "cookie")] {
+            for param in params {
+                parameters.push(json!({
+                    "name": param.name,
+                    "in": location,
+                    "required": param.required,
+                    "schema": openapi::ty_schema(param.ty),
+                }));
+            }
+        }
+
+        let response = |res: ResponseTapi| {
+            let content = match res {
+                ResponseTapi::PlainText => Some(("text/plain", json!({ "type": "string" }))),
+                ResponseTapi::Bytes => Some((
+                    "application/octet-stream",
+                    json!({ "type": "string", "contentEncoding": "binary" }),
+                )),
+                ResponseTapi::Json(ty) => Some(("application/json", openapi::ty_schema(ty))),
+                ResponseTapi::Html => Some(("text/html", json!({ "type": "string" }))),
+                ResponseTapi::Sse(ty) => Some(("text/event-stream", openapi::ty_schema(ty))),
+                ResponseTapi::None => None,
+            };
+            match content {
+                Some((mime, schema)) => json!({
+                    "description": "",
+                    "content": { mime: { "schema": schema } },
+                }),
+                None => json!({ "description": "" }),
+            }
+        };
+        let responses = self.responses();
+        let responses = if responses.is_empty() {
+            json!({ "200": response(self.res()) })
+        } else {
+            responses
+                .into_iter()
+                .map(|(status, res)| (status.to_string(), response(res)))
+                .collect()
+        };
+
+        let mut operation = json!({
+            "operationId": self.name(),
+            "parameters": parameters,
+            "responses": responses,
+        });
+        if let Some(request_body) = request_body {
+            operation["requestBody"] = request_body;
+        }
+        if let Some(group) = self.group() {
+            operation["tags"] = json!([group]);
+        }
+        if let Some(error) = self.error() {
+            let mime = if error.id() == String::id() {
+                "text/plain"
+            } else {
+                "application/json"
+            };
+            operation["responses"]["default"] = json!({
+                "description": "",
+                "content": { mime: { "schema": openapi::ty_schema(error) } },
+            });
+        }
+        operation
+    }
 }
-impl<'a, AppState, T> Endpoint<AppState> for &'a T
+/// The `in: query` parameters of `ty`, with flattened struct fields in place.
+fn query_params(ty: DynTapi, name: &str) -> Vec<serde_json::Value> {
+    use serde_json::json;
+
+    let s = match ty.kind() {
+        TypeKind::Struct(s) if !s.attr.transparent => s,
+        _ => {
+            return vec![json!({
+                "name": name,
+                "in": "query",
+                "style": "form",
+                "explode": true,
+                "schema": openapi::ty_schema(ty),
+            })]
+        }
+    };
+    let mut parameters = Vec::new();
+    for f in ts::defaulted_fields(&s) {
+        let FieldName::Named(name) = &f.name else {
+            continue;
+        };
+        if f.attr.skip_deserializing {
+            continue;
+        }
+        if f.attr.flatten {
+            parameters.extend(query_params(f.ty, &name.deserialize_name));
+            continue;
+        }
+        parameters.push(json!({
+            "name": name.deserialize_name,
+            "in": "query",
+            "required": !matches!(f.ty.kind(), TypeKind::Option(_))
+                && matches!(f.attr.default, crate::kind::Default::None),
+            "schema": openapi::ty_schema(f.ty),
+        }));
+    }
+    parameters
+}
+
+/// The `:param` and `*param` segments of `path`, with their types if known.
+fn path_params(path: &str, ty: Option<DynTapi>) -> Vec<(&str, Option<DynTapi>)> {
+    path.split('/')
+        .filter_map(|p| p.strip_prefix(':').or_else(|| p.strip_prefix('*')))
+        .enumerate()
+        .map(|(idx, name)| {
+            let ty = ty.and_then(|ty| match ty.kind() {
+                TypeKind::Tuple(fields) => fields.get(idx).copied(),
+                TypeKind::Struct(s) => s
+                    .fields
+                    .iter()
+                    .find(|f| matches!(&f.name, FieldName::Named(n) if n.deserialize_name == name))
+                    .map(|f| f.ty),
+                _ => Some(ty),
+            });
+            (name, ty)
+        })
+        .collect()
+}
+
+/// The TypeScript type of the request body.
+fn ts_req_ty(body: Option<RequestStructureBody>) -> String {
+    match body {
+        Some(RequestStructureBody::Query(ty))
+        | Some(RequestStructureBody::Json(ty))
+        | Some(RequestStructureBody::Form(ty)) => ts::full_view_name(ty, View::Input),
+        Some(RequestStructureBody::Multipart(Some(ty))) => {
+            format!("MultipartFields<{}>", ts::full_view_name(ty, View::Input))
+        }
+        Some(RequestStructureBody::Multipart(None)) => "FormData".to_string(),
+        Some(RequestStructureBody::Bytes) => "Blob | ArrayBuffer | Uint8Array".to_string(),
+        Some(RequestStructureBody::PlainText) => "string".to_string(),
+        None => "Record<string, never>".to_string(),
+    }
+}
+
+/// The `RequestType` of the prelude.
+fn req_ty(body: Option<RequestStructureBody>) -> &'static str {
+    match body {
+        Some(RequestStructureBody::Query(_)) => "query",
+        Some(RequestStructureBody::Json(_)) => "json",
+        Some(RequestStructureBody::Form(_)) => "form",
+        Some(RequestStructureBody::Multipart(_)) => "multipart",
+        Some(RequestStructureBody::Bytes) => "bytes",
+        Some(RequestStructureBody::PlainText) => "text",
+        None => "none",
+    }
+}
+
+/// The `RequestOptions` of the prelude, or nothing if left at the defaults.
+fn request_options(req: &RequestStructure, error: Option<DynTapi>) -> String {
+    let mut options = Vec::new();
+    if error.is_some() {
+        options.push("typedErrors: true".to_string());
+    }
+    for (params, key) in [(&req.headers, "headers"), (&req.cookies, "cookies")] {
+        if !params.is_empty() {
+            options.push(format!(
+                "{key}: [{}]",
+                params
+                    .iter()
+                    .map(|param| format!("{:?}", param.name))
+                    .format(", ")
+            ));
+        }
+    }
+    if options.is_empty() {
+        String::new()
+    } else {
+        format!(", {{ {} }}", options.iter().format(", "))
+    }
+}
+
+/// The `ResponseType` of the prelude.
+fn res_ty(res: ResponseTapi) -> &'static str {
+    match res {
+        ResponseTapi::PlainText => "text",
+        ResponseTapi::Bytes => "bytes",
+        ResponseTapi::Json(_) => "json",
+        ResponseTapi::Html => "html",
+        ResponseTapi::Sse(_) => "sse",
+        ResponseTapi::None => "none",
+    }
+}
+
+/// The result type of a `requestStatus` and the `ResponseType` of each status.
+fn ts_responses(responses: &[(u16, ResponseTapi)]) -> (String, String) {
+    let res = responses
+        .iter()
+        .map(|(status, res)| {
+            format!(
+                "{{ status: {status}; data: {} }}",
+                ts::full_ty_name(res.ty())
+            )
+        })
+        .join(" | ");
+    let res_tys = format!(
+        "{{ {} }}",
+        responses
+            .iter()
+            .map(|(status, res)| format!("{status}: {:?}", res_ty(*res)))
+            .format(", ")
+    );
+    (res, res_tys)
+}
+
+/// The parameters and error of a `request` as extra type arguments.
+fn ts_extra_generics(path: &str, req: &RequestStructure, error: Option<DynTapi>) -> String {
+    let params = path_params(path, req.path)
+        .into_iter()
+        .map(|(name, ty)| {
+            let ty = ty.map_or_else(
+                || "string".to_string(),
+                |ty| ts::full_view_name(ty, View::Input),
+            );
+            format!("{name}: {ty}")
+        })
+        .chain(req.headers.iter().chain(&req.cookies).map(|param| {
+            format!(
+                "{:?}{}: {}",
+                param.name,
+                if param.required { "" } else { "?" },
+                ts::full_view_name(param.ty, View::Input)
+            )
+        }))
+        .collect::<Vec<_>>();
+    let params = match (params.is_empty(), error) {
+        (true, None) => return String::new(),
+        (true, Some(_)) => ", never".to_string(),
+        (false, _) => format!(", {{ {} }}", params.iter().format(", ")),
+    };
+    match error {
+        Some(error) => format!("{params}, {}", ts::full_ty_name(error)),
+        None => params,
+    }
+}
+
+impl<AppState, T> Endpoint<AppState> for &T
 where
     T: Endpoint<AppState>,
 {
@@ -279,6 +671,12 @@ where
     fn method(&self) -> Method {
         (*self).method()
     }
+    fn name(&self) -> String {
+        (*self).name()
+    }
+    fn group(&self) -> Option<&'static str> {
+        (*self).group()
+    }
     fn bind_to(&self, router: axum::Router<AppState>) -> axum::Router<AppState> {
         (*self).bind_to(router)
     }
@@ -288,23 +686,52 @@ where
     fn res(&self) -> ResponseTapi {
         (*self).res()
     }
+    fn error(&self) -> Option<DynTapi> {
+        (*self).error()
+    }
+    fn responses(&self) -> Vec<(u16, ResponseTapi)> {
+        (*self).responses()
+    }
+}
+
+/// How endpoints are laid out in the generated `api` object.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ClientLayout {
+    /// `api.getUsersId`
+    #[default]
+    Flat,
+    /// `api.users.byId.get`
+    Path,
+    /// `api.users.getUsersId`, by [`Endpoint::group`]
+    Group,
+}
+
+enum ClientNode<'a, AppState> {
+    Endpoint(&'a dyn Endpoint<AppState>),
+    Group(IndexMap<String, ClientNode<'a, AppState>>),
 }
 
 pub struct Endpoints<'a, AppState> {
     endpoints: Vec<&'a dyn Endpoint<AppState>>,
     extra_tys: Vec<DynTapi>,
+    layout: ClientLayout,
 }
 impl<'a, AppState> Endpoints<'a, AppState> {
     pub fn new(endpoints: impl IntoIterator<Item = &'a dyn Endpoint<AppState>>) -> Self {
         Self {
             endpoints: endpoints.into_iter().collect(),
             extra_tys: Vec::new(),
+            layout: ClientLayout::Flat,
         }
     }
     pub fn with_ty<T: Tapi + 'static>(mut self) -> Self {
         self.extra_tys.push(T::boxed());
         self
     }
+    pub fn with_layout(mut self, layout: ClientLayout) -> Self {
+        self.layout = layout;
+        self
+    }
     pub fn tys(&self) -> Vec<DynTapi> {
         let mut tys = self.extra_tys.clone();
         for endpoint in &self.endpoints {
@@ -314,29 +741,151 @@ impl<'a, AppState> Endpoints<'a, AppState> {
         tys.dedup_by_key(|t| t.id());
         transitive_closure(tys)
     }
-    pub fn ts_client(&self) -> String {
-        let mut s = ts::builder().types(self.tys());
-
-        s.push_str("export const api = {\n");
+    /// The endpoints by their [`Endpoint::name`], which must be unique.
+    fn named(&self) -> Result<Vec<(String, &'a dyn Endpoint<AppState>)>, Error> {
+        let describe = |e: &dyn Endpoint<AppState>| format!("{} {}", e.method().as_str(), e.path());
+        let mut seen = std::collections::HashMap::new();
         for endpoint in &self.endpoints {
-            let name = heck::AsLowerCamelCase(endpoint.path()).to_string();
-            let name = if name.is_empty() { "index" } else { &name };
-            s.push_str(&format!("    {name}: {},\n", endpoint.ts_client()));
+            if let Some(other) = seen.insert(endpoint.name(), endpoint) {
+                return Err(Error::DuplicateEndpoint {
+                    name: endpoint.name(),
+                    endpoints: [describe(*other), describe(*endpoint)],
+                });
+            }
         }
-        s.push_str("};\n");
-        s
+        Ok(self.endpoints.iter().map(|e| (e.name(), *e)).collect())
     }
-    pub fn js_client(&self) -> String {
-        let mut s = js::builder().types(self.tys());
+    /// The endpoints arranged according to the [`ClientLayout`].
+    fn client_tree(&self) -> Result<IndexMap<String, ClientNode<'a, AppState>>, Error> {
+        fn insert<'a, AppState>(
+            node: &mut IndexMap<String, ClientNode<'a, AppState>>,
+            keys: &[String],
+            endpoint: &'a dyn Endpoint<AppState>,
+        ) -> bool {
+            let (key, rest) = keys.split_first().unwrap();
+            if rest.is_empty() {
+                return node
+                    .insert(key.clone(), ClientNode::Endpoint(endpoint))
+                    .is_none();
+            }
+            match node
+                .entry(key.clone())
+                .or_insert_with(|| ClientNode::Group(IndexMap::new()))
+            {
+                ClientNode::Group(group) => insert(group, rest, endpoint),
+                ClientNode::Endpoint(_) => false,
+            }
+        }
 
-        s.push_str("export const api = {\n");
-        for endpoint in &self.endpoints {
-            let name = heck::AsLowerCamelCase(endpoint.path()).to_string();
-            let name = if name.is_empty() { "index" } else { &name };
-            s.push_str(&format!("  {name}: {},\n", endpoint.js_client()));
+        let mut root = IndexMap::new();
+        for (name, endpoint) in self.named()? {
+            if let Some(RequestStructureBody::Query(ty) | RequestStructureBody::Form(ty)) =
+                endpoint.body().body
+            {
+                error::check_form(ty)?;
+            }
+            let keys = match self.layout {
+                ClientLayout::Flat => vec![name],
+                ClientLayout::Path => endpoint
+                    .path()
+                    .split('/')
+                    .filter(|p| !p.is_empty())
+                    .map(
+                        |p| match p.strip_prefix(':').or_else(|| p.strip_prefix('*')) {
+                            Some(param) => {
+                                heck::AsLowerCamelCase(format!("by {param}")).to_string()
+                            }
+                            None => heck::AsLowerCamelCase(p).to_string(),
+                        },
+                    )
+                    .chain([endpoint.method().as_str().to_lowercase()])
+                    .collect(),
+                ClientLayout::Group => endpoint
+                    .group()
+                    .into_iter()
+                    .flat_map(|g| g.split('.'))
+                    .map(|g| g.to_string())
+                    .chain([name])
+                    .collect(),
+            };
+            if !insert(&mut root, &keys, endpoint) {
+                return Err(Error::ClientKeyTaken {
+                    key: keys.join("."),
+                    endpoint: format!("{} {}", endpoint.method().as_str(), endpoint.path()),
+                });
+            }
         }
+        Ok(root)
+    }
+    /// Writes the `api` object, with `client` generating each endpoint.
+    fn write_api(
+        &self,
+        step: &str,
+        client: impl Fn(&dyn Endpoint<AppState>) -> String,
+    ) -> Result<String, Error> {
+        fn write<AppState>(
+            s: &mut String,
+            node: &IndexMap<String, ClientNode<'_, AppState>>,
+            depth: usize,
+            step: &str,
+            client: &dyn Fn(&dyn Endpoint<AppState>) -> String,
+        ) {
+            let indent = step.repeat(depth);
+            for (key, node) in node {
+                match node {
+                    ClientNode::Endpoint(endpoint) => {
+                        // NOTE: the clients are written for the top level, so
+                        // any following lines are indented to match
+                        let nested = format!("\n{}", step.repeat(depth - 1));
+                        let client = client(*endpoint).replace('\n', &nested);
+                        s.push_str(&format!("{indent}{key}: {client},\n"));
+                    }
+                    ClientNode::Group(group) => {
+                        s.push_str(&format!("{indent}{key}: {{\n"));
+                        write(s, group, depth + 1, step, client);
+                        s.push_str(&format!("{indent}}},\n"));
+                    }
+                }
+            }
+        }
+
+        let mut s = "export const api = {\n".to_string();
+        write(&mut s, &self.client_tree()?, 1, step, &client);
         s.push_str("};\n");
-        s
+        Ok(s)
+    }
+    /// Generate the types and an `api` object of all endpoints in TypeScript.
+    pub fn ts_client(&self) -> Result<String, Error> {
+        let mut s = ts::builder().types(self.tys())?;
+        s.push_str(&self.write_api("    ", |endpoint| endpoint.ts_client())?);
+        Ok(s)
+    }
+    /// Like [`Endpoints::ts_client`], but in JavaScript with JSDoc types.
+    pub fn js_client(&self) -> Result<String, Error> {
+        let mut s = js::builder().types(self.tys())?;
+        s.push_str(&self.write_api("  ", |endpoint| endpoint.js_client())?);
+        Ok(s)
+    }
+    /// Generate an OpenAPI 3.1 document describing all endpoints.
+    pub fn openapi(&self, title: &str, version: &str) -> Result<serde_json::Value, Error> {
+        let mut paths = serde_json::Map::new();
+        for (_, endpoint) in self.named()? {
+            let item = paths
+                .entry(endpoint.openapi_path())
+                .or_insert_with(|| serde_json::json!({}));
+            item[endpoint.method().as_str().to_lowercase()] = endpoint.openapi_operation();
+        }
+        Ok(serde_json::json!({
+            "openapi": "3.1.0",
+            "info": { "title": title, "version": version },
+            "paths": paths,
+            "components": { "schemas": openapi::schemas(self.tys())? },
+        }))
+    }
+    #[cfg(feature = "serde_yaml")]
+    pub fn openapi_yaml(&self, title: &str, version: &str) -> Result<String, Error> {
+        // NOTE: JSON values always have a YAML representation
+        Ok(serde_yaml::to_string(&self.openapi(title, version)?).unwrap())
     }
 }
 impl<'a, AppState> IntoIterator for Endpoints<'a, AppState> {
@@ -399,6 +948,12 @@ pub enum RequestTapi {
     Path(DynTapi),
     Query(DynTapi),
     Json(DynTapi),
+    Form(DynTapi),
+    Multipart(Option<DynTapi>),
+    Bytes,
+    PlainText,
+    Header(RequestParam),
+    Cookie(RequestParam),
     None,
 }
 pub trait RequestTapiExtractor {
@@ -429,6 +984,194 @@ impl<T> RequestTapiExtractor for axum::extract::State<T> {
         RequestTapi::None
     }
 }
+impl<T: Tapi + 'static> RequestTapiExtractor for axum::Form<T> {
+    fn extract_request() -> RequestTapi {
+        RequestTapi::Form(<T as Tapi>::boxed())
+    }
+}
+impl RequestTapiExtractor for axum::body::Bytes {
+    fn extract_request() -> RequestTapi {
+        RequestTapi::Bytes
+    }
+}
+impl RequestTapiExtractor for String {
+    fn extract_request() -> RequestTapi {
+        RequestTapi::PlainText
+    }
+}
+#[cfg(feature = "multipart")]
+impl RequestTapiExtractor for axum::extract::Multipart {
+    fn extract_request() -> RequestTapi {
+        RequestTapi::Multipart(None)
+    }
+}
+#[cfg(feature = "multipart")]
+impl<T: Tapi + 'static> RequestTapiExtractor for TypedMultipart<T> {
+    fn extract_request() -> RequestTapi {
+        RequestTapi::Multipart(Some(<T as Tapi>::boxed()))
+    }
+}
+#[cfg(feature = "axum-extra")]
+impl<H: axum_extra::headers::Header> RequestTapiExtractor for axum_extra::TypedHeader<H> {
+    fn extract_request() -> RequestTapi {
+        RequestTapi::Header(RequestParam {
+            name: H::name().as_str(),
+            ty: <String as Tapi>::boxed(),
+            required: true,
+        })
+    }
+}
+impl<N: ParamName, T: Tapi + 'static> RequestTapiExtractor for Header<N, T> {
+    fn extract_request() -> RequestTapi {
+        RequestTapi::Header(RequestParam {
+            name: N::NAME,
+            ty: <T as Tapi>::boxed(),
+            required: true,
+        })
+    }
+}
+impl<N: ParamName, T: Tapi + 'static> RequestTapiExtractor for Cookie<N, T> {
+    fn extract_request() -> RequestTapi {
+        RequestTapi::Cookie(RequestParam {
+            name: N::NAME,
+            ty: <T as Tapi>::boxed(),
+            required: true,
+        })
+    }
+}
+/// Headers and cookies extracted as `Option`s are optional parameters.
+impl<T: RequestTapiExtractor> RequestTapiExtractor for Option<T> {
+    fn extract_request() -> RequestTapi {
+        match T::extract_request() {
+            RequestTapi::Header(param) => RequestTapi::Header(RequestParam {
+                required: false,
+                ..param
+            }),
+            RequestTapi::Cookie(param) => RequestTapi::Cookie(RequestParam {
+                required: false,
+                ..param
+            }),
+            req => req,
+        }
+    }
+}
+
+/// The name of a [`Header`] or [`Cookie`], which parses its value with
+/// [`FromStr`] and rejects requests where it is missing or invalid with
+/// `400 Bad Request`, unless extracted as an `Option`.
+///
+/// ```ignore
+/// struct ApiKey;
+/// impl ParamName for ApiKey {
+///     const NAME: &'static str = "x-api-key";
+/// }
+///
+/// #[tapi(path = "/secret", method = Get)]
+/// async fn secret(Header(key, _): Header<ApiKey>) -> String { .. }
+/// ```
+///
+/// [`FromStr`]: std::str::FromStr
+pub trait ParamName {
+    const NAME: &'static str;
+}
+
+/// Extracts the header named by `N`, see [`ParamName`].
+pub struct Header<N, T = String>(pub T, pub std::marker::PhantomData<fn() -> N>);
+/// Extracts the cookie named by `N`, see [`ParamName`].
+pub struct Cookie<N, T = String>(pub T, pub std::marker::PhantomData<fn() -> N>);
+
+fn parse_param<T>(kind: &str, name: &str, value: Option<&str>) -> Result<T, ParamRejection>
+where
+    T: std::str::FromStr,
+    T::Err: std::fmt::Display,
+{
+    let value = value.ok_or_else(|| ParamRejection(format!("missing {kind} `{name}`")))?;
+    value
+        .parse()
+        .map_err(|err| ParamRejection(format!("invalid {kind} `{name}`: {err}")))
+}
+
+/// The rejection of [`Header`] and [`Cookie`].
+#[derive(Debug)]
+pub struct ParamRejection(String);
+impl axum::response::IntoResponse for ParamRejection {
+    fn into_response(self) -> axum::response::Response {
+        (axum::http::StatusCode::BAD_REQUEST, self.0).into_response()
+    }
+}
+
+#[axum::async_trait]
+impl<N, T, S> axum::extract::FromRequestParts<S> for Header<N, T>
+where
+    N: ParamName,
+    T: std::str::FromStr,
+    T::Err: std::fmt::Display,
+    S: Send + Sync,
+{
+    type Rejection = ParamRejection;
+
+    async fn from_request_parts(
+        parts: &mut axum::http::request::Parts,
+        _state: &S,
+    ) -> Result<Self, Self::Rejection> {
+        let value = parts
+            .headers
+            .get(N::NAME)
+            .map(|value| value.to_str())
+            .transpose()
+            .map_err(|err| ParamRejection(format!("invalid header `{}`: {err}", N::NAME)))?;
+        let value = parse_param("header", N::NAME, value)?;
+        Ok(Self(value, std::marker::PhantomData))
+    }
+}
+#[axum::async_trait]
+impl<N, T, S> axum::extract::FromRequestParts<S> for Cookie<N, T>
+where
+    N: ParamName,
+    T: std::str::FromStr,
+    T::Err: std::fmt::Display,
+    S: Send + Sync,
+{
+    type Rejection = ParamRejection;
+
+    async fn from_request_parts(
+        parts: &mut axum::http::request::Parts,
+        _state: &S,
+    ) -> Result<Self, Self::Rejection> {
+        let value = parts
+            .headers
+            .get_all(axum::http::header::COOKIE)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(';'))
+            .filter_map(|cookie| cookie.trim().split_once('='))
+            .find(|(name, _)| *name == N::NAME)
+            .map(|(_, value)| value);
+        let value = parse_param("cookie", N::NAME, value)?;
+        Ok(Self(value, std::marker::PhantomData))
+    }
+}
+
+/// A [`Multipart`](axum::extract::Multipart) body whose fields are described
+/// by `T`.
+#[cfg(feature = "multipart")]
+pub struct TypedMultipart<T>(
+    pub axum::extract::Multipart,
+    std::marker::PhantomData<fn() -> T>,
+);
+#[cfg(feature = "multipart")]
+#[axum::async_trait]
+impl<T, S> axum::extract::FromRequest<S> for TypedMultipart<T>
+where
+    S: Send + Sync,
+{
+    type Rejection = axum::extract::multipart::MultipartRejection;
+
+    async fn from_request(req: axum::extract::Request, state: &S) -> Result<Self, Self::Rejection> {
+        let multipart = axum::extract::Multipart::from_request(req, state).await?;
+        Ok(Self(multipart, std::marker::PhantomData))
+    }
+}
 
 #[derive(Debug, Clone, Copy)]
 pub enum ResponseTapi {
@@ -482,6 +1225,92 @@ impl<T: Tapi + 'static> ResponseTapiExtractor for Sse<T> {
         ResponseTapi::Sse(<T as Tapi>::boxed())
     }
 }
+impl ResponseTapiExtractor for axum::response::Redirect {
+    fn extract_response() -> ResponseTapi {
+        ResponseTapi::None
+    }
+}
+impl ResponseTapiExtractor for axum::http::StatusCode {
+    fn extract_response() -> ResponseTapi {
+        ResponseTapi::None
+    }
+}
+macro_rules! impl_response_parts {
+    ($($part:ty),*) => {
+        impl<T: ResponseTapiExtractor> ResponseTapiExtractor for ($($part,)* T) {
+            fn extract_response() -> ResponseTapi {
+                T::extract_response()
+            }
+        }
+    };
+}
+impl_response_parts!(axum::http::StatusCode);
+impl_response_parts!(axum::http::HeaderMap);
+impl_response_parts!(axum::http::StatusCode, axum::http::HeaderMap);
+
+/// The error type `E` of a handler returning `Result<T, E>`.
+pub trait ErrorTapiExtractor {
+    fn extract_error() -> Option<DynTapi>;
+}
+
+/// Resolves to [`TypedError`] or [`UntypedError`] by autoref, for `#[tapi]`
+/// handlers.
+#[doc(hidden)]
+pub struct ErrorOf<R>(std::marker::PhantomData<fn() -> R>);
+impl<R> ErrorOf<R> {
+    #[allow(clippy::new_without_default)]
+    pub fn new() -> Self {
+        Self(std::marker::PhantomData)
+    }
+}
+#[doc(hidden)]
+pub trait TypedError {
+    fn extract_error(&self) -> Option<DynTapi>;
+}
+impl<T, E: ErrorTapiExtractor> TypedError for &ErrorOf<Result<T, E>> {
+    fn extract_error(&self) -> Option<DynTapi> {
+        E::extract_error()
+    }
+}
+#[doc(hidden)]
+pub trait UntypedError {
+    fn extract_error(&self) -> Option<DynTapi>;
+}
+impl<R> UntypedError for ErrorOf<R> {
+    fn extract_error(&self) -> Option<DynTapi> {
+        None
+    }
+}
+impl<E: Tapi + axum::response::IntoResponse + 'static> ErrorTapiExtractor for E {
+    fn extract_error() -> Option<DynTapi> {
+        Some(E::boxed())
+    }
+}
+impl<E: Tapi + 'static> ErrorTapiExtractor for axum::Json<E> {
+    fn extract_error() -> Option<DynTapi> {
+        Some(E::boxed())
+    }
+}
+impl<E: Tapi + 'static> ErrorTapiExtractor for (axum::http::StatusCode, axum::Json<E>) {
+    fn extract_error() -> Option<DynTapi> {
+        Some(E::boxed())
+    }
+}
+impl ErrorTapiExtractor for (axum::http::StatusCode, String) {
+    fn extract_error() -> Option<DynTapi> {
+        Some(String::boxed())
+    }
+}
+impl ErrorTapiExtractor for axum::http::StatusCode {
+    fn extract_error() -> Option<DynTapi> {
+        None
+    }
+}
+impl ErrorTapiExtractor for axum::response::Response {
+    fn extract_error() -> Option<DynTapi> {
+        None
+    }
+}
 
 impl RequestTapi {
     pub fn ty(self) -> DynTapi {
@@ -489,6 +1318,11 @@ impl RequestTapi {
             Self::Path(ty) => ty,
             Self::Query(ty) => ty,
             Self::Json(ty) => ty,
+            Self::Form(ty) => ty,
+            Self::Multipart(ty) => ty.unwrap_or_else(<() as Tapi>::boxed),
+            Self::Bytes => <Vec<u8> as Tapi>::boxed(),
+            Self::PlainText => <String as Tapi>::boxed(),
+            Self::Header(param) | Self::Cookie(param) => param.ty,
             Self::None => <() as Tapi>::boxed(),
         }
     }
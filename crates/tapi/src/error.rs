@@ -0,0 +1,196 @@
+use crate::{
+    builder::NameCollision,
+    kind::{Field, FieldName, TypeKind, VariantKind},
+    DynTapi,
+};
+
+/// Why types or clients could not be generated.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    /// Two types would be declared under the same name.
+    NameCollision(NameCollision),
+    /// A type has a shape that cannot be declared.
+    Unsupported {
+        /// The Rust type, e.g. `api::User`.
+        ty: &'static str,
+        /// The field at fault, if any.
+        field: Option<String>,
+        reason: &'static str,
+    },
+    /// Two endpoints share a name, as in `GET /users` and `POST /users`.
+    DuplicateEndpoint {
+        name: String,
+        endpoints: [String; 2],
+    },
+    /// An endpoint is placed at a key of the `api` object that is taken.
+    ClientKeyTaken { key: String, endpoint: String },
+}
+
+impl Error {
+    fn unsupported(ty: DynTapi, field: Option<String>, reason: &'static str) -> Self {
+        Error::Unsupported {
+            ty: ty.type_name(),
+            field,
+            reason,
+        }
+    }
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::NameCollision(err) => err.fmt(f),
+            Error::Unsupported {
+                ty,
+                field: Some(field),
+                reason,
+            } => write!(f, "field `{field}` of `{ty}` {reason}"),
+            Error::Unsupported {
+                ty,
+                field: None,
+                reason,
+            } => write!(f, "`{ty}` {reason}"),
+            Error::DuplicateEndpoint { name, endpoints } => write!(
+                f,
+                "endpoints `{}` and `{}` are both named `{name}`, \
+                 use `#[tapi(name = \"...\")]` to tell them apart",
+                endpoints[0], endpoints[1],
+            ),
+            Error::ClientKeyTaken { key, endpoint } => {
+                write!(f, "client key `api.{key}` of `{endpoint}` is already taken")
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<NameCollision> for Error {
+    fn from(err: NameCollision) -> Self {
+        Error::NameCollision(err)
+    }
+}
+
+/// The field a `#[serde(transparent)]` struct is serialized as in `view`.
+pub(crate) fn transparent_field(
+    ty: DynTapi,
+    fields: &[Field],
+    view: crate::kind::View,
+) -> Result<&Field, Error> {
+    fields
+        .iter()
+        .find(|f| !f.attr.skipped(view))
+        .ok_or_else(|| {
+            Error::unsupported(
+                ty,
+                None,
+                "is `#[serde(transparent)]`, but every field is skipped",
+            )
+        })
+}
+
+/// Checks that the fields of the structs and struct variants of `ty` are named.
+pub(crate) fn check_fields(ty: DynTapi) -> Result<(), Error> {
+    let check = |fields: &[Field]| {
+        fields.iter().try_for_each(|f| match f.name {
+            FieldName::Named(_) => Ok(()),
+            FieldName::Index(idx) => Err(Error::unsupported(
+                ty,
+                Some(idx.to_string()),
+                "has no name, but belongs to a struct with named fields",
+            )),
+        })
+    };
+    match ty.kind() {
+        TypeKind::Struct(s) => check(&s.fields),
+        TypeKind::Enum(e) => e.variants.iter().try_for_each(|v| match &v.kind {
+            VariantKind::Struct(fields) => check(fields),
+            VariantKind::Unit | VariantKind::Tuple(_) => Ok(()),
+        }),
+        _ => Ok(()),
+    }
+}
+
+/// Checks that `ty` can be sent as a query string or form, i.e. is made of
+/// key-value pairs with scalar values.
+///
+/// Flattened fields and enums are buffered by serde, which then only decodes
+/// strings, so their fields must be strings or unit enums.
+#[cfg(feature = "endpoints")]
+pub(crate) fn check_form(ty: DynTapi) -> Result<(), Error> {
+    use crate::kind::{BuiltinTypeKind, TagType, View};
+
+    fn scalar(ty: DynTapi, buffered: bool) -> bool {
+        match ty.kind() {
+            TypeKind::Builtin(BuiltinTypeKind::String | BuiltinTypeKind::Char) => true,
+            TypeKind::Builtin(_) => !buffered,
+            TypeKind::Option(ty) => scalar(ty, buffered),
+            TypeKind::Enum(e) => {
+                matches!(e.attr.tag, TagType::External)
+                    && e.variants
+                        .iter()
+                        .all(|v| matches!(v.kind, VariantKind::Unit))
+            }
+            TypeKind::Struct(s) if s.attr.transparent => {
+                transparent_field(ty, &s.fields, View::Input).is_ok_and(|f| scalar(f.ty, buffered))
+            }
+            TypeKind::TupleStruct(s) => match &s.fields[..] {
+                [field] => scalar(field.ty, buffered),
+                _ => false,
+            },
+            _ => false,
+        }
+    }
+    fn fields(ty: DynTapi, fields: &[Field], buffered: bool) -> Result<(), Error> {
+        for f in fields.iter().filter(|f| !f.attr.skipped(View::Input)) {
+            if f.attr.flatten {
+                pairs(f.ty, true)?;
+            } else if !scalar(f.ty, buffered) {
+                let field = match &f.name {
+                    FieldName::Named(name) => name.deserialize_name.clone(),
+                    FieldName::Index(idx) => idx.to_string(),
+                };
+                let reason = if buffered && scalar(f.ty, false) {
+                    "is flattened or in an enum, where a query string or form only holds strings"
+                } else {
+                    "cannot be encoded in a query string or form, which only holds scalar values"
+                };
+                return Err(Error::unsupported(ty, Some(field), reason));
+            }
+        }
+        Ok(())
+    }
+    fn pairs(ty: DynTapi, buffered: bool) -> Result<(), Error> {
+        match ty.kind() {
+            TypeKind::Struct(s) if s.attr.transparent => {
+                pairs(transparent_field(ty, &s.fields, View::Input)?.ty, buffered)
+            }
+            TypeKind::Struct(s) => fields(ty, &s.fields, buffered),
+            TypeKind::Option(inner) => pairs(inner, buffered),
+            TypeKind::Record(k, v) if scalar(k, true) && scalar(v, buffered) => Ok(()),
+            TypeKind::Enum(e) if matches!(e.attr.tag, TagType::Internal { .. } | TagType::None) => {
+                e.variants.iter().try_for_each(|v| match &v.kind {
+                    VariantKind::Unit => Ok(()),
+                    VariantKind::Struct(fs) => fields(ty, fs, true),
+                    VariantKind::Tuple(tys) => tys.iter().try_for_each(|ty| pairs(*ty, true)),
+                })
+            }
+            TypeKind::List(item) if !buffered => match item.kind() {
+                TypeKind::Tuple(kv) if kv.len() == 2 && kv.iter().all(|ty| scalar(*ty, false)) => {
+                    Ok(())
+                }
+                _ => Err(Error::unsupported(
+                    ty,
+                    None,
+                    "cannot be encoded in a query string or form, which only holds key-value pairs",
+                )),
+            },
+            _ => Err(Error::unsupported(
+                ty,
+                None,
+                "cannot be encoded in a query string or form, which only holds key-value pairs",
+            )),
+        }
+    }
+    pairs(ty, false)
+}
@@ -33,6 +33,25 @@ pub struct Name {
     pub deserialize_name: String,
 }
 
+impl Name {
+    /// The name used in `view`.
+    pub fn get(&self, view: View) -> &str {
+        match view {
+            View::Input => &self.deserialize_name,
+            View::Output => &self.serialize_name,
+        }
+    }
+}
+
+/// The side of serde a type is looked at from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum View {
+    /// What `Deserialize` accepts.
+    Input,
+    /// What `Serialize` produces.
+    Output,
+}
+
 #[derive(Debug, Clone)]
 pub enum Default {
     None,
@@ -66,6 +85,8 @@ pub enum TypeKind {
     Builtin(BuiltinTypeKind),
     Record(DynTapi, DynTapi),
     Any,
+    /// A type parameter of a generic declaration.
+    Param(String),
 }
 
 #[derive(Debug, Clone)]
@@ -94,12 +115,14 @@ pub enum BuiltinTypeKind {
 pub struct Struct {
     pub attr: ContainerAttributes,
     pub fields: Vec<Field>,
+    pub docs: Option<String>,
 }
 
 #[derive(Debug, Clone)]
 pub struct TupleStruct {
     pub attr: ContainerAttributes,
     pub fields: Vec<TupleStructField>,
+    pub docs: Option<String>,
 }
 
 #[derive(Debug, Clone)]
@@ -113,6 +136,7 @@ pub struct Field {
     pub attr: FieldAttributes,
     pub name: FieldName,
     pub ty: DynTapi,
+    pub docs: Option<String>,
 }
 
 #[derive(Clone)]
@@ -145,7 +169,8 @@ pub struct FieldAttributes {
     pub aliases: BTreeSet<String>,
     pub skip_serializing: bool,
     pub skip_deserializing: bool,
-    // pub skip_serializing_if: Option<ExprPath>,
+    /// The path of the `skip_serializing_if` predicate.
+    pub skip_serializing_if: Option<String>,
     pub default: Default,
     // pub serialize_with: Option<ExprPath>,
     // pub deserialize_with: Option<ExprPath>,
@@ -157,16 +182,28 @@ pub struct FieldAttributes {
     pub transparent: bool,
 }
 
+impl FieldAttributes {
+    /// Whether the field is left out entirely in `view`.
+    pub fn skipped(&self, view: View) -> bool {
+        match view {
+            View::Input => self.skip_deserializing,
+            View::Output => self.skip_serializing,
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct Enum {
     pub attr: ContainerAttributes,
     pub variants: Vec<EnumVariant>,
+    pub docs: Option<String>,
 }
 
 #[derive(Debug, Clone)]
 pub struct EnumVariant {
     pub name: String,
     pub kind: VariantKind,
+    pub docs: Option<String>,
 }
 
 #[derive(Debug, Clone)]
@@ -1,6 +1,7 @@
 pub mod builder;
 #[cfg(feature = "endpoints")]
 pub mod endpoints;
+mod error;
 pub mod kind;
 pub mod targets;
 
@@ -15,9 +16,14 @@ use std::{
     sync::Arc,
 };
 
+pub use error::Error;
 use indexmap::{IndexMap, IndexSet};
 use kind::{BuiltinTypeKind, TypeKind};
 pub use tapi_macro::{tapi, Tapi};
+/// Used by `#[tapi(tracing)]` to instrument handlers.
+#[cfg(feature = "tracing")]
+#[doc(hidden)]
+pub use tracing;
 
 pub trait Tapi: 'static {
     fn name() -> &'static str;
@@ -44,8 +50,21 @@ pub trait Tapi: 'static {
             TypeKind::Builtin(_) => Vec::new(),
             TypeKind::Record(k, v) => vec![k, v],
             TypeKind::Any => Vec::new(),
+            TypeKind::Param(_) => Vec::new(),
         }
     }
+    /// The type arguments, e.g. `[User]` for `Page<User>`.
+    fn generics() -> Vec<DynTapi> {
+        Vec::new()
+    }
+    /// The declaration shared by all instantiations, e.g. `Page<T>` for
+    /// `Page<User>`.
+    fn generic_decl() -> DynTapi
+    where
+        Self: Sized,
+    {
+        Self::boxed()
+    }
     fn path() -> Vec<&'static str> {
         let mut path = std::any::type_name::<Self>()
             .split('<')
@@ -74,10 +93,16 @@ pub trait Tapi: 'static {
 
 pub trait TapiDyn: std::fmt::Debug {
     fn name(&self) -> &'static str;
+    /// The full name of the Rust type, for error messages.
+    fn type_name(&self) -> &'static str {
+        self.name()
+    }
     fn id(&self) -> std::any::TypeId;
     fn kind(&self) -> TypeKind;
     fn dependencies(&self) -> Vec<DynTapi>;
     fn path(&self) -> Vec<&'static str>;
+    fn generics(&self) -> Vec<DynTapi>;
+    fn generic_decl(&self) -> DynTapi;
 }
 
 pub type DynTapi = &'static dyn TapiDyn;
@@ -86,6 +111,9 @@ impl<T: Tapi> TapiDyn for TypedWrap<T> {
     fn name(&self) -> &'static str {
         <T as Tapi>::name()
     }
+    fn type_name(&self) -> &'static str {
+        std::any::type_name::<T>()
+    }
     fn id(&self) -> std::any::TypeId {
         <T as Tapi>::id()
     }
@@ -98,6 +126,12 @@ impl<T: Tapi> TapiDyn for TypedWrap<T> {
     fn path(&self) -> Vec<&'static str> {
         <T as Tapi>::path()
     }
+    fn generics(&self) -> Vec<DynTapi> {
+        <T as Tapi>::generics()
+    }
+    fn generic_decl(&self) -> DynTapi {
+        <T as Tapi>::generic_decl()
+    }
 }
 
 pub struct TypedWrap<T>(PhantomData<T>);
@@ -106,6 +140,11 @@ impl<T> TypedWrap<T> {
         Self(PhantomData)
     }
 }
+impl<T> Default for TypedWrap<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 impl<T> Clone for TypedWrap<T> {
     fn clone(&self) -> Self {
         Self(PhantomData)
@@ -117,6 +156,92 @@ impl<T> std::fmt::Debug for TypedWrap<T> {
     }
 }
 
+/// `R` with its type arguments replaced by `Args`, without instantiating it
+/// with stand-ins. Used by `#[derive(Tapi)]`.
+#[doc(hidden)]
+pub struct Applied<R, Args>(PhantomData<(R, Args)>);
+impl<R: Tapi, Args: TapiList + 'static> Applied<R, Args> {
+    /// Whether `R` has a generic declaration of its own.
+    fn declared() -> bool {
+        R::generic_decl().id() != R::id()
+    }
+}
+impl<R: Tapi, Args: TapiList + 'static> Tapi for Applied<R, Args> {
+    fn name() -> &'static str {
+        R::name()
+    }
+    fn kind() -> TypeKind {
+        if Self::declared() {
+            return R::generic_decl().kind();
+        }
+        match (R::kind(), &Args::boxed_all()[..]) {
+            (TypeKind::List(_), [item, ..]) => TypeKind::List(*item),
+            (TypeKind::Option(_), [inner, ..]) => TypeKind::Option(*inner),
+            (TypeKind::Record(_, _), [k, v, ..]) => TypeKind::Record(*k, *v),
+            (kind, _) => kind,
+        }
+    }
+    fn dependencies() -> Vec<DynTapi> {
+        if Self::declared() {
+            let mut deps = R::generic_decl().dependencies();
+            deps.extend(Args::boxed_all());
+            deps
+        } else {
+            match Self::kind() {
+                TypeKind::List(item) => vec![item],
+                TypeKind::Option(inner) => vec![inner],
+                TypeKind::Record(k, v) => vec![k, v],
+                _ => R::dependencies(),
+            }
+        }
+    }
+    fn generics() -> Vec<DynTapi> {
+        if Self::declared() {
+            Args::boxed_all()
+        } else {
+            R::generics()
+        }
+    }
+    fn generic_decl() -> DynTapi {
+        if Self::declared() {
+            R::generic_decl()
+        } else {
+            Self::boxed()
+        }
+    }
+    fn path() -> Vec<&'static str> {
+        R::path()
+    }
+    fn boxed() -> DynTapi {
+        // NOTE: transparent wrappers like `Box<T>` are their contents
+        match &Args::boxed_all()[..] {
+            [inner] if R::id() != std::any::TypeId::of::<R>() => *inner,
+            _ => &TypedWrap::<Self>(PhantomData),
+        }
+    }
+}
+
+/// The types of a tuple, boxed. Used by `#[derive(Tapi)]`.
+#[doc(hidden)]
+pub trait TapiList {
+    fn boxed_all() -> Vec<DynTapi>;
+}
+macro_rules! impl_tapi_list {
+    ($($ty:ident),*) => {
+        impl<$($ty: 'static + Tapi),*> TapiList for ($($ty,)*) {
+            fn boxed_all() -> Vec<DynTapi> {
+                vec![$(<$ty as Tapi>::boxed()),*]
+            }
+        }
+    };
+}
+impl_tapi_list!(A);
+impl_tapi_list!(A, B);
+impl_tapi_list!(A, B, C);
+impl_tapi_list!(A, B, C, D);
+impl_tapi_list!(A, B, C, D, E);
+impl_tapi_list!(A, B, C, D, E, F);
+
 macro_rules! impl_typed {
     ($($ty:ty = $ts_name:literal & $kind:expr,)*) => {
         $(
@@ -157,6 +282,36 @@ macro_rules! impl_generic {
         )*
     };
 }
+/// Wrappers that are the same type as their contents.
+macro_rules! impl_transparent {
+    ($($ty:ident),*) => {
+        $(
+            impl<T: Tapi + 'static> Tapi for $ty<T> {
+                fn name() -> &'static str {
+                    T::name()
+                }
+                fn id() -> std::any::TypeId {
+                    T::id()
+                }
+                fn kind() -> TypeKind {
+                    T::kind()
+                }
+                fn dependencies() -> Vec<DynTapi> {
+                    T::dependencies()
+                }
+                fn generics() -> Vec<DynTapi> {
+                    T::generics()
+                }
+                fn generic_decl() -> DynTapi {
+                    T::generic_decl()
+                }
+                fn path() -> Vec<&'static str> {
+                    T::path()
+                }
+            }
+        )*
+    };
+}
 impl_typed!(
     () = "unknown" & BuiltinTypeKind::Unit,
     String = "string" & BuiltinTypeKind::String,
@@ -194,16 +349,12 @@ impl_typed!(
 impl_typed!(smol_str::SmolStr = "string" & BuiltinTypeKind::String,);
 impl_generic!(
     Vec = "{}[]" & "z.array({})" & TypeKind::List(T::boxed()),
-    Option = "({} | null)" & "z.optional({})" & TypeKind::Option(T::boxed()),
+    Option = "({} | null)" & "z.nullable({})" & TypeKind::Option(T::boxed()),
     HashSet = "{}[]" & "z.array({})" & TypeKind::List(T::boxed()),
     BTreeSet = "{}[]" & "z.array({})" & TypeKind::List(T::boxed()),
     IndexSet = "{}[]" & "z.array({})" & TypeKind::List(T::boxed()),
-    Box = "{}" & "{}" & T::kind(),
-    Rc = "{}" & "{}" & T::kind(),
-    Arc = "{}" & "{}" & T::kind(),
-    Cell = "{}" & "{}" & T::kind(),
-    RefCell = "{}" & "{}" & T::kind(),
 );
+impl_transparent!(Box, Rc, Arc, Cell, RefCell);
 impl<const N: usize, T: Tapi + 'static> Tapi for [T; N] {
     fn name() -> &'static str {
         std::any::type_name::<[T; N]>()
@@ -218,6 +369,21 @@ impl<const N: usize, T: Tapi + 'static> Tapi for [T; N] {
         Vec::new()
     }
 }
+/// Serialized as `()`, whatever `T` is.
+impl<T: ?Sized + 'static> Tapi for PhantomData<T> {
+    fn name() -> &'static str {
+        std::any::type_name::<Self>()
+    }
+    fn id() -> std::any::TypeId {
+        std::any::TypeId::of::<Self>()
+    }
+    fn kind() -> TypeKind {
+        TypeKind::Builtin(BuiltinTypeKind::Unit)
+    }
+    fn path() -> Vec<&'static str> {
+        Vec::new()
+    }
+}
 impl<K: 'static + Tapi, V: 'static + Tapi> Tapi for HashMap<K, V> {
     fn name() -> &'static str {
         std::any::type_name::<Self>()
@@ -298,22 +464,18 @@ impl Tapi for serde_json::Value {
     }
 }
 
-fn transitive_closure(mut closure: Vec<DynTapi>) -> Vec<DynTapi> {
-    let mut next = Vec::new();
-    loop {
-        for c in &closure {
-            next.extend(c.dependencies().into_iter());
-        }
-        let mut done = true;
-        for n in next.drain(..) {
-            if closure.iter().all(|m| m.id() != n.id()) {
-                done = false;
-                closure.push(n);
-            }
-        }
-        if done {
-            break;
-        }
+/// `tys` and all the types they depend on, each once, breadth first.
+fn transitive_closure(tys: Vec<DynTapi>) -> Vec<DynTapi> {
+    let mut seen = HashSet::new();
+    let mut closure = tys
+        .into_iter()
+        .filter(|ty| seen.insert(ty.id()))
+        .collect::<Vec<_>>();
+    let mut next = 0;
+    while let Some(ty) = closure.get(next) {
+        let deps = ty.dependencies();
+        closure.extend(deps.into_iter().filter(|dep| seen.insert(dep.id())));
+        next += 1;
     }
     closure
 }
@@ -1,3 +1,6 @@
 pub mod fs;
 pub mod js;
+pub mod json_schema;
+pub mod openapi;
 pub mod ts;
+pub mod zod;
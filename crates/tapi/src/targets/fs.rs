@@ -2,8 +2,9 @@ use itertools::Itertools;
 
 use crate::{
     builder::TypesBuilder,
+    error,
     kind::{BuiltinTypeKind, Field, TagType, TypeKind, VariantKind},
-    DynTapi,
+    DynTapi, Error,
 };
 
 pub fn builder() -> TypesBuilder {
@@ -27,14 +28,15 @@ pub fn ty_name(ty: DynTapi) -> String {
     use BuiltinTypeKind::*;
 
     match ty.kind() {
-        TypeKind::Struct(s) => s.attr.name.serialize_name,
-        TypeKind::TupleStruct(s) => s.attr.name.serialize_name,
-        TypeKind::Enum(e) => e.attr.name.serialize_name,
+        TypeKind::Struct(_) | TypeKind::TupleStruct(_) | TypeKind::Enum(_) => {
+            format!("{}{}", super::ts::decl_name(ty), fs_generics(ty))
+        }
         TypeKind::List(ty) => format!("List<{}>", full_ty_name(ty)),
         TypeKind::Option(ty) => format!("Option<{}>", full_ty_name(ty)),
         TypeKind::Tuple(fields) => fs_tuple(&fields),
         TypeKind::Record(k, v) => format!("Map<{}, {}>", full_ty_name(k), full_ty_name(v)),
         TypeKind::Any => "any".to_string(),
+        TypeKind::Param(name) => format!("'{name}"),
         TypeKind::Builtin(b) => match b {
             U8 => "uint8",
             U16 => "uint16",
@@ -59,86 +61,111 @@ pub fn ty_name(ty: DynTapi) -> String {
     }
 }
 
-pub fn ty_decl(ty: DynTapi) -> Option<String> {
+pub fn ty_decl(ty: DynTapi) -> Result<Option<String>, Error> {
     use std::fmt::Write;
-    fn inner(ty: DynTapi) -> Result<Option<String>, std::fmt::Error> {
-        Ok(Some(match ty.kind() {
-            TypeKind::Struct(s) => {
-                let fs_fields = fs_fields(&s.fields);
-                format!("type {} =\n  {{ {fs_fields} }}", s.attr.name.serialize_name)
-            }
-            TypeKind::TupleStruct(s) => {
-                let fs_fields = fs_tuple(&s.fields.iter().map(|f| f.ty).collect_vec());
-                format!("type {} = {fs_fields}", s.attr.name.serialize_name)
-            }
-            TypeKind::Enum(e) => {
-                let mut out = String::new();
-                let encoding = [
-                    "JsonUnionEncoding.ExternalTag",
-                    "JsonUnionEncoding.UnwrapFieldlessTags",
-                    "JsonUnionEncoding.UnwrapSingleFieldCases",
-                ];
-
-                let converter_options = match &e.attr.tag {
-                    TagType::External => vec![format!(
+    let ty = ty.generic_decl();
+    error::check_fields(ty)?;
+    let decl = match ty.kind() {
+        TypeKind::Struct(s) => {
+            let fs_fields = fs_fields(&s.fields);
+            format!("type {} =\n  {{ {fs_fields} }}", ty_name(ty))
+        }
+        TypeKind::TupleStruct(s) => {
+            let fs_fields = fs_tuple(&s.fields.iter().map(|f| f.ty).collect_vec());
+            format!("type {} = {fs_fields}", ty_name(ty))
+        }
+        TypeKind::Enum(e) => {
+            let mut out = String::new();
+            let encoding = [
+                "JsonUnionEncoding.ExternalTag",
+                "JsonUnionEncoding.UnwrapFieldlessTags",
+                "JsonUnionEncoding.UnwrapSingleFieldCases",
+            ];
+
+            let converter_options = match &e.attr.tag {
+                TagType::External => vec![format!(
+                    "BaseUnionEncoding = {}",
+                    encoding.iter().format(" + ")
+                )],
+                TagType::Internal { tag } => vec![
+                    format!(
                         "BaseUnionEncoding = {}",
-                        encoding.iter().format(" + ")
-                    )],
-                    TagType::Internal { tag } => vec![
-                        format!(
-                            "BaseUnionEncoding = {}",
-                            ["JsonUnionEncoding.UnwrapSingleFieldCases"]
-                                .iter()
-                                .format(" + ")
-                        ),
-                        format!("UnionTagName = {tag:?}"),
-                    ],
-                    TagType::Adjacent { tag, content } => vec![
-                        format!(
-                            "BaseUnionEncoding = {}",
-                            ["JsonUnionEncoding.UnwrapSingleFieldCases"]
-                                .iter()
-                                .format(" + ")
-                        ),
-                        format!("UnionTagName = {tag:?}"),
-                        format!("UnionFieldsName = {content:?}"),
-                    ],
-                    TagType::None => vec![format!(
+                        ["JsonUnionEncoding.UnwrapSingleFieldCases"]
+                            .iter()
+                            .format(" + ")
+                    ),
+                    format!("UnionTagName = {tag:?}"),
+                ],
+                TagType::Adjacent { tag, content } => vec![
+                    format!(
                         "BaseUnionEncoding = {}",
-                        encoding.iter().format(" + ")
-                    )],
-                };
-
-                writeln!(
-                    out,
-                    "[<JsonFSharpConverter({})>]",
-                    converter_options.iter().format(", "),
-                )?;
-                writeln!(out, "type {} =", e.attr.name.serialize_name)?;
-
-                for v in &e.variants {
-                    match &v.kind {
-                        VariantKind::Unit => writeln!(out, "  | {}", v.name)?,
-                        VariantKind::Tuple(fields) => {
-                            writeln!(out, "  | {} of {}", v.name, fs_tuple(fields))?
-                        }
-                        VariantKind::Struct(fields) => {
-                            writeln!(out, "  | {} of {}", v.name, fs_named_tuple(fields))?
-                        }
+                        ["JsonUnionEncoding.UnwrapSingleFieldCases"]
+                            .iter()
+                            .format(" + ")
+                    ),
+                    format!("UnionTagName = {tag:?}"),
+                    format!("UnionFieldsName = {content:?}"),
+                ],
+                TagType::None => vec![format!(
+                    "BaseUnionEncoding = {}",
+                    [
+                        "JsonUnionEncoding.Untagged",
+                        "JsonUnionEncoding.UnwrapSingleFieldCases",
+                    ]
+                    .iter()
+                    .format(" + ")
+                )],
+            };
+
+            writeln!(
+                out,
+                "[<JsonFSharpConverter({})>]",
+                converter_options.iter().format(", "),
+            )
+            .unwrap();
+            writeln!(out, "type {} =", ty_name(ty)).unwrap();
+
+            for v in &e.variants {
+                write!(out, "{}", fs_docs(v.docs.as_deref(), "  ")).unwrap();
+                match &v.kind {
+                    VariantKind::Unit => writeln!(out, "  | {}", v.name),
+                    VariantKind::Tuple(fields) => {
+                        writeln!(out, "  | {} of {}", v.name, fs_tuple(fields))
+                    }
+                    VariantKind::Struct(fields) => {
+                        writeln!(out, "  | {} of {}", v.name, fs_named_tuple(fields))
                     }
                 }
-
-                out
+                .unwrap();
             }
-            TypeKind::List(_)
-            | TypeKind::Option(_)
-            | TypeKind::Tuple(_)
-            | TypeKind::Record(_, _)
-            | TypeKind::Any
-            | TypeKind::Builtin(_) => return Ok(None),
-        }))
+
+            out
+        }
+        TypeKind::List(_)
+        | TypeKind::Option(_)
+        | TypeKind::Tuple(_)
+        | TypeKind::Record(_, _)
+        | TypeKind::Any
+        | TypeKind::Param(_)
+        | TypeKind::Builtin(_) => return Ok(None),
+    };
+    Ok(Some(format!(
+        "{}{decl}",
+        fs_docs(super::ts::type_docs(ty).as_deref(), "")
+    )))
+}
+
+/// The type arguments of a generic instantiation, e.g. `<int32, string>`.
+fn fs_generics(ty: DynTapi) -> String {
+    let generics = ty.generics();
+    if generics.is_empty() {
+        String::new()
+    } else {
+        format!(
+            "<{}>",
+            generics.iter().map(|g| full_ty_name(*g)).format(", ")
+        )
     }
-    inner(ty).unwrap()
 }
 
 fn fs_tuple(fields: &[DynTapi]) -> String {
@@ -148,23 +175,85 @@ fn fs_tuple(fields: &[DynTapi]) -> String {
 fn fs_named_tuple(fields: &[Field]) -> String {
     format!(
         "{}",
-        fields
+        fs_flattened_fields(fields, false)
             .iter()
-            .map(|f| format!("{}: {}", f.attr.name.serialize_name, full_ty_name(f.ty)))
+            .map(|f| format!("{}: {}", f.name, f.ty))
             .format(" * ")
     )
 }
 
 fn fs_fields(fields: &[crate::kind::Field]) -> impl std::fmt::Display + '_ {
-    fields
-        .iter()
-        .filter(|f| !f.attr.skip_serializing)
+    fs_flattened_fields(fields, false)
+        .into_iter()
         .map(|f| {
-            let name = match &f.name {
-                crate::kind::FieldName::Named(n) => &n.serialize_name,
-                crate::kind::FieldName::Index(_) => todo!(),
-            };
-            format!("{name}: {}", full_ty_name(f.ty))
+            let docs = fs_docs(f.docs.as_deref(), "")
+                .lines()
+                .map(|l| format!("{l}\n    "))
+                .collect::<String>();
+            if f.extension_data {
+                format!("{docs}[<JsonExtensionData>] {}: {}", f.name, f.ty)
+            } else {
+                format!("{docs}{}: {}", f.name, f.ty)
+            }
         })
         .format("\n    ")
 }
+
+struct FsField {
+    name: String,
+    ty: String,
+    extension_data: bool,
+    docs: Option<String>,
+}
+
+/// `docs` as `///` comment lines, each prefixed by `indent`.
+fn fs_docs(docs: Option<&str>, indent: &str) -> String {
+    docs.iter()
+        .flat_map(|docs| docs.lines())
+        .map(|l| format!("{indent}/// {l}").trim_end().to_string() + "\n")
+        .collect()
+}
+
+/// Inlines the fields of flattened structs, as F# records cannot flatten.
+fn fs_flattened_fields(fields: &[Field], optional: bool) -> Vec<FsField> {
+    let mut out = Vec::new();
+    for f in fields.iter().filter(|f| !f.attr.skip_serializing) {
+        let name = match &f.name {
+            crate::kind::FieldName::Named(n)
+                if super::ts::is_identifier(&n.serialize_name)
+                    && !n.serialize_name.contains('$') =>
+            {
+                n.serialize_name.clone()
+            }
+            crate::kind::FieldName::Named(n) => format!("``{}``", n.serialize_name),
+            crate::kind::FieldName::Index(idx) => idx.to_string(),
+        };
+        let (ty, optional) = match f.ty.kind() {
+            TypeKind::Option(ty) if f.attr.flatten => (ty, true),
+            _ => (f.ty, optional),
+        };
+        match ty.kind() {
+            TypeKind::Struct(s) if f.attr.flatten && !s.attr.transparent => {
+                out.extend(fs_flattened_fields(&s.fields, optional));
+            }
+            TypeKind::Record(_, _) if f.attr.flatten => out.push(FsField {
+                name,
+                ty: "System.Collections.Generic.Dictionary<string, System.Text.Json.JsonElement>"
+                    .to_string(),
+                extension_data: true,
+                docs: f.docs.clone(),
+            }),
+            _ => out.push(FsField {
+                name,
+                ty: if optional {
+                    format!("Option<{}>", full_ty_name(ty))
+                } else {
+                    full_ty_name(ty)
+                },
+                extension_data: false,
+                docs: f.docs.clone(),
+            }),
+        }
+    }
+    out
+}
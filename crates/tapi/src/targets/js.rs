@@ -2,8 +2,9 @@ use itertools::Itertools;
 
 use crate::{
     builder::TypesBuilder,
-    kind::{TagType, TypeKind, VariantKind},
-    DynTapi,
+    error,
+    kind::{Field, FieldName, TagType, TypeKind, VariantKind, View},
+    DynTapi, Error,
 };
 
 use super::ts;
@@ -27,121 +28,239 @@ pub fn ty_name(ty: DynTapi) -> String {
     ts::ty_name(ty)
 }
 
-pub fn ty_decl(ty: DynTapi) -> Option<String> {
+pub fn ty_decl(ty: DynTapi) -> Result<Option<String>, Error> {
+    let ty = ty.generic_decl();
+    if ts::differs(ty) {
+        let Some(input) = view_decl(ty, View::Input, "Input")? else {
+            return Ok(None);
+        };
+        let Some(output) = view_decl(ty, View::Output, "Output")? else {
+            return Ok(None);
+        };
+        let js_header = js_header(ty);
+        Ok(Some(format!(
+            "{input}\n{output}\n/**\n{js_header} * @typedef {{{}Output{}}} {} */",
+            typedef_name(ty),
+            ts::ts_generics(ty, View::Output),
+            typedef_name(ty),
+        )))
+    } else {
+        view_decl(ty, View::Output, "")
+    }
+}
+
+/// The declaration of `ty` as seen from `view`, named with `suffix`.
+pub fn view_decl(ty: DynTapi, view: View, suffix: &str) -> Result<Option<String>, Error> {
     use std::fmt::Write;
-    fn inner(ty: DynTapi) -> Result<Option<String>, std::fmt::Error> {
-        Ok(Some(match ty.kind() {
-            TypeKind::Struct(s) => {
-                if s.attr.transparent {
+    error::check_fields(ty)?;
+    let name = format!("{}{suffix}", typedef_name(ty));
+    Ok(Some(match ty.kind() {
+        TypeKind::Struct(s) => {
+            if s.attr.transparent {
+                format!(
+                    "{}export type {}{suffix}{} = {};",
+                    ts::jsdoc(s.docs.as_deref(), ""),
+                    ts::decl_name(ty),
+                    ts::ts_generics(ty, view),
+                    ts::view_name(error::transparent_field(ty, &s.fields, view)?.ty, view),
+                )
+            } else {
+                let fields = ts::defaulted_fields(&s);
+                let js_fields = js_fields(false, &fields, view);
+                let js_flatten = js_flatten(&fields, view);
+                // NOTE: properties can only be documented with `@property`,
+                // which cannot express intersections nor quoted names
+                if js_flatten.is_empty()
+                    && fields.iter().any(|f| f.docs.is_some())
+                    && fields.iter().all(|f| match &f.name {
+                        FieldName::Named(n) => ts::is_identifier(n.get(view)),
+                        FieldName::Index(_) => true,
+                    })
+                {
                     format!(
-                        "export type {} = {};",
-                        s.attr.name.serialize_name,
-                        ty_name(
-                            s.fields
-                                .iter()
-                                .find(|f| !f.attr.skip_serializing)
-                                .unwrap()
-                                .ty
-                        ),
+                        "/**\n{} * @typedef {{object}} {name}\n{} */",
+                        js_header(ty),
+                        js_properties(&fields, view),
                     )
                 } else {
-                    let js_fields = js_fields(false, &s.fields);
                     format!(
-                        "/**\n * @typedef {{{{ {js_fields} }}}} {} */",
-                        full_ty_name(ty),
+                        "/**\n{} * @typedef {{{{ {js_fields} }}{js_flatten}}} {name} */",
+                        js_header(ty),
                     )
                 }
             }
-            TypeKind::TupleStruct(s) => {
-                let js_fields = js_tuple(&s.fields.iter().map(|f| f.ty).collect_vec());
-                format!("export type {} = {js_fields};", s.attr.name.serialize_name,)
-            }
-            TypeKind::Enum(e) => {
-                let mut out = String::new();
+        }
+        TypeKind::TupleStruct(s) => {
+            let js_fields = js_tuple(&s.fields.iter().map(|f| f.ty).collect_vec(), view);
+            format!(
+                "{}export type {}{suffix}{} = {js_fields};",
+                ts::jsdoc(s.docs.as_deref(), ""),
+                ts::decl_name(ty),
+                ts::ts_generics(ty, view),
+            )
+        }
+        TypeKind::Enum(e) => {
+            let mut out = String::new();
 
-                let has_data = e
-                    .variants
-                    .iter()
-                    .any(|v| matches!(&v.kind, VariantKind::Tuple(_) | VariantKind::Struct(_)));
+            let has_data = e
+                .variants
+                .iter()
+                .any(|v| matches!(&v.kind, VariantKind::Tuple(_) | VariantKind::Struct(_)));
 
-                let variants = e.variants.iter().map(|v| match &v.kind {
+            let variants = e
+                .variants
+                .iter()
+                .map(|v| match &v.kind {
                     VariantKind::Unit => match &e.attr.tag {
                         TagType::External => format!("{:?}", v.name),
                         TagType::Internal { tag } | TagType::Adjacent { tag, content: _ } => {
                             format!("{{ {tag:?}: {:?} }}", v.name)
                         }
-                        TagType::None => todo!("{}:{}", file!(), line!()),
+                        TagType::None => "null".to_string(),
                     },
                     VariantKind::Tuple(fields) => match &e.attr.tag {
                         TagType::External => {
-                            format!("{{ {:?}: {} }}", v.name, js_tuple(fields))
+                            format!("{{ {:?}: {} }}", v.name, js_tuple(fields, view))
                         }
-                        TagType::Internal { tag: _ } => {
-                            unreachable!("tagged tuples are not allowed by serde")
+                        // NOTE: serde only allows newtype variants here
+                        TagType::Internal { tag } => {
+                            format!("{{ {tag:?}: {:?} }} & {}", v.name, js_tuple(fields, view))
                         }
                         TagType::Adjacent { tag, content } => {
                             format!(
                                 "{{ {tag:?}: {:?}, {content:?}: {} }}",
                                 v.name,
-                                js_tuple(fields),
+                                js_tuple(fields, view),
                             )
                         }
-                        TagType::None => todo!("{}:{}", file!(), line!()),
+                        TagType::None => js_tuple(fields, view),
                     },
-                    VariantKind::Struct(fields) => match &e.attr.tag {
-                        TagType::External => {
-                            let js_fields = js_fields(false, fields);
-                            format!("{{ {:?}: {{ {js_fields} }} }}", v.name)
-                        }
-                        TagType::Internal { tag } => {
-                            let js_fields = js_fields(false, fields);
-                            format!("{{ {tag:?}: {:?}, {js_fields} }}", v.name)
-                        }
-                        TagType::Adjacent { tag, content } => {
-                            let js_fields = js_fields(false, fields);
-                            format!(
-                                "{{ {tag:?}: {:?}, {content:?}: {{ {js_fields} }} }}",
+                    VariantKind::Struct(fields) => {
+                        let js_fields = js_fields(false, fields, view);
+                        let js_flatten = js_flatten(fields, view);
+                        match &e.attr.tag {
+                            TagType::External => {
+                                format!("{{ {:?}: {{ {js_fields} }}{js_flatten} }}", v.name)
+                            }
+                            TagType::Internal { tag } => {
+                                format!("{{ {tag:?}: {:?}, {js_fields} }}{js_flatten}", v.name)
+                            }
+                            TagType::Adjacent { tag, content } => format!(
+                                "{{ {tag:?}: {:?}, {content:?}: {{ {js_fields} }}{js_flatten} }}",
                                 v.name
-                            )
+                            ),
+                            TagType::None => format!("{{ {js_fields} }}{js_flatten}"),
                         }
-                        TagType::None => todo!("TagType::None @ {}:{}", file!(), line!()),
-                    },
-                });
+                    }
+                })
+                // NOTE: untagged unit variants all become `null`
+                .unique();
 
+            let js_header = js_header(ty);
+            if js_header.is_empty() {
                 write!(
                     out,
-                    "/** @typedef {{{}}} {} */",
-                    variants.clone().clone().format(" | "),
-                    full_ty_name(ty),
-                )?;
-
-                // write!(out, "{};", variants.clone().format("\n  | "))?;
-                if !has_data {
-                    write!(
-                        out,
-                        "\nexport const {} = /** @type {{{}[]}} */ ([{}]);",
-                        heck::AsShoutySnakeCase(&e.attr.name.serialize_name),
-                        full_ty_name(ty),
-                        variants.format(", "),
-                    )?;
-                }
-                out
+                    "/** @typedef {{{}}} {name} */",
+                    variants.clone().format(" | "),
+                )
+                .unwrap();
+            } else {
+                write!(
+                    out,
+                    "/**\n{js_header} * @typedef {{{}}} {name} */",
+                    variants.clone().format(" | "),
+                )
+                .unwrap();
             }
-            TypeKind::List(_)
-            | TypeKind::Option(_)
-            | TypeKind::Tuple(_)
-            | TypeKind::Record(_, _)
-            | TypeKind::Any
-            | TypeKind::Builtin(_) => return Ok(None),
-        }))
+
+            // NOTE: untagged unit variants all serialize as `null`, so
+            // there is nothing meaningful to enumerate
+            if !has_data && !matches!(e.attr.tag, TagType::None) {
+                write!(
+                    out,
+                    "\nexport const {} = /** @type {{{name}[]}} */ ([{}]);",
+                    heck::AsShoutySnakeCase(&e.attr.name.serialize_name),
+                    variants.format(", "),
+                )
+                .unwrap();
+            }
+            out
+        }
+        TypeKind::List(_)
+        | TypeKind::Option(_)
+        | TypeKind::Tuple(_)
+        | TypeKind::Record(_, _)
+        | TypeKind::Any
+        | TypeKind::Param(_)
+        | TypeKind::Builtin(_) => return Ok(None),
+    }))
+}
+
+/// The name a typedef is declared under, without type arguments.
+fn typedef_name(ty: DynTapi) -> String {
+    let mut name = ts::decl_name(ty);
+    for p in ty.path().iter().rev() {
+        name = format!("{}.{}", p, name);
     }
-    inner(ty).unwrap()
+    name
+}
+
+/// The doc comment and `@template` tags preceding a `@typedef`.
+fn js_header(ty: DynTapi) -> String {
+    let docs = ts::type_docs(ty)
+        .iter()
+        .flat_map(|docs| {
+            docs.lines()
+                .map(|l| format!(" * {l}").trim_end().to_string() + "\n")
+        })
+        .collect::<String>()
+        .replace("*/", "*\\/");
+    let templates = ty
+        .generics()
+        .iter()
+        .map(|g| format!(" * @template {}\n", ty_name(*g)))
+        .collect::<String>();
+    docs + &templates
+}
+
+/// The `@property` tags of the fields of an object typedef.
+fn js_properties(fields: &[Field], view: View) -> String {
+    fields
+        .iter()
+        .filter(|f| !f.attr.flatten)
+        .filter_map(|f| {
+            let (name, ty) = ts::ts_field(f, view)?;
+            let name = match name.strip_suffix('?') {
+                Some(name) => format!("[{name}]"),
+                None => name,
+            };
+            let docs = match &f.docs {
+                Some(docs) => format!(
+                    " - {}",
+                    docs.replace("*/", "*\\/").lines().map(str::trim).join(" ")
+                ),
+                None => String::new(),
+            };
+            Some(format!(" * @property {{{ty}}} {name}{docs}\n"))
+        })
+        .collect()
+}
+
+fn js_tuple(fields: &[DynTapi], view: View) -> String {
+    ts::ts_tuple(fields, view)
 }
 
-fn js_tuple(fields: &[DynTapi]) -> String {
-    ts::ts_tuple(fields)
+fn js_fields(multi_line: bool, fields: &[Field], view: View) -> String {
+    // NOTE: the fields are rendered inside a comment, which cannot contain
+    // another one
+    let fields = fields
+        .iter()
+        .cloned()
+        .map(|f| Field { docs: None, ..f })
+        .collect_vec();
+    ts::ts_fields(multi_line, &fields, view)
 }
 
-fn js_fields(multi_line: bool, fields: &[crate::kind::Field]) -> impl std::fmt::Display + '_ {
-    ts::ts_fields(multi_line, fields)
+fn js_flatten(fields: &[Field], view: View) -> String {
+    ts::ts_flatten(fields, view)
 }
@@ -0,0 +1,312 @@
+use std::collections::HashMap;
+
+use serde_json::{json, Map, Value};
+
+use crate::{
+    builder::NameCollision,
+    error,
+    kind::{BuiltinTypeKind, Field, FieldName, TagType, TypeKind, VariantKind, View},
+    transitive_closure, DynTapi, Error,
+};
+
+pub const DRAFT: &str = "https://json-schema.org/draft/2020-12/schema";
+pub const REF_PREFIX: &str = "#/$defs/";
+
+/// A JSON Schema document for `ty`.
+pub fn schema(ty: DynTapi) -> Result<Value, Error> {
+    let mut root = Map::new();
+    root.insert("$schema".to_string(), json!(DRAFT));
+    match ty_schema(ty, REF_PREFIX) {
+        Value::Object(schema) => root.extend(schema),
+        schema => {
+            root.insert("allOf".to_string(), json!([schema]));
+        }
+    }
+    root.insert(
+        "$defs".to_string(),
+        Value::Object(defs(transitive_closure(vec![ty]), REF_PREFIX)?),
+    );
+    Ok(Value::Object(root))
+}
+
+/// The key under `$defs` for a named type, e.g. `Page_User` for `Page<User>`.
+pub fn def_name(ty: DynTapi) -> String {
+    let mut name = match ty.kind() {
+        TypeKind::Struct(s) => s.attr.name.serialize_name,
+        TypeKind::TupleStruct(s) => s.attr.name.serialize_name,
+        TypeKind::Enum(e) => e.attr.name.serialize_name,
+        _ => ty.name().to_string(),
+    };
+    for arg in ty.generics() {
+        name.push('_');
+        name.push_str(&arg_name(arg));
+    }
+    let mut path = ty.path().join(".");
+    if !path.is_empty() {
+        path.push('.');
+    }
+    path + &name
+}
+
+/// A name for `ty` as the type argument of a generic [`def_name`].
+fn arg_name(ty: DynTapi) -> String {
+    match ty.kind() {
+        TypeKind::Struct(_) | TypeKind::TupleStruct(_) | TypeKind::Enum(_) => def_name(ty),
+        TypeKind::List(ty) => format!("Array_{}", arg_name(ty)),
+        TypeKind::Option(ty) => format!("Nullable_{}", arg_name(ty)),
+        TypeKind::Tuple(fields) => format!(
+            "Tuple_{}",
+            fields
+                .iter()
+                .map(|f| arg_name(*f))
+                .collect::<Vec<_>>()
+                .join("_")
+        ),
+        TypeKind::Record(k, v) => format!("Record_{}_{}", arg_name(k), arg_name(v)),
+        TypeKind::Any => "Any".to_string(),
+        TypeKind::Param(name) => name,
+        TypeKind::Builtin(b) => format!("{b:?}").to_lowercase(),
+    }
+}
+
+/// The schema used when referring to `ty`.
+pub fn ty_schema(ty: DynTapi, ref_prefix: &str) -> Value {
+    match ty.kind() {
+        TypeKind::Struct(_) | TypeKind::TupleStruct(_) | TypeKind::Enum(_) => {
+            json!({ "$ref": format!("{ref_prefix}{}", def_name(ty)) })
+        }
+        TypeKind::List(ty) => json!({ "type": "array", "items": ty_schema(ty, ref_prefix) }),
+        TypeKind::Option(ty) => {
+            json!({ "anyOf": [ty_schema(ty, ref_prefix), { "type": "null" }] })
+        }
+        TypeKind::Tuple(fields) => tuple_schema(&fields, ref_prefix),
+        TypeKind::Record(k, v) => {
+            let mut schema = json!({
+                "type": "object",
+                "additionalProperties": ty_schema(v, ref_prefix),
+            });
+            // NOTE: only enums constrain the keys, everything else is
+            // serialized as an arbitrary string key
+            if let TypeKind::Enum(_) = k.kind() {
+                schema["propertyNames"] = ty_schema(k, ref_prefix);
+            }
+            schema
+        }
+        TypeKind::Any | TypeKind::Param(_) => json!({}),
+        TypeKind::Builtin(b) => builtin_schema(b),
+    }
+}
+
+/// The schema placed under `$defs` for named types.
+pub fn ty_decl(ty: DynTapi, ref_prefix: &str) -> Result<Option<Value>, Error> {
+    error::check_fields(ty)?;
+    Ok(Some(match ty.kind() {
+        TypeKind::Struct(s) => {
+            if s.attr.transparent {
+                ty_schema(
+                    error::transparent_field(ty, &s.fields, View::Output)?.ty,
+                    ref_prefix,
+                )
+            } else {
+                Value::Object(object_schema(&s.fields, ref_prefix))
+            }
+        }
+        TypeKind::TupleStruct(s) => tuple_schema(
+            &s.fields.iter().map(|f| f.ty).collect::<Vec<_>>(),
+            ref_prefix,
+        ),
+        TypeKind::Enum(e) => {
+            let is_unit = e
+                .variants
+                .iter()
+                .all(|v| matches!(v.kind, VariantKind::Unit));
+            if is_unit && matches!(e.attr.tag, TagType::External) {
+                json!({
+                    "type": "string",
+                    "enum": e.variants.iter().map(|v| v.name.as_str()).collect::<Vec<_>>(),
+                })
+            } else {
+                let variants = e.variants.iter().map(|v| {
+                    let payload = match &v.kind {
+                        VariantKind::Unit => None,
+                        VariantKind::Tuple(fields) => Some(tuple_schema(fields, ref_prefix)),
+                        VariantKind::Struct(fields) => {
+                            Some(Value::Object(object_schema(fields, ref_prefix)))
+                        }
+                    };
+                    match (&e.attr.tag, payload) {
+                        (TagType::External, None) => json!({ "const": v.name }),
+                        (TagType::External, Some(payload)) => json!({
+                            "type": "object",
+                            "properties": { &v.name: payload },
+                            "required": [v.name],
+                            "additionalProperties": false,
+                        }),
+                        (TagType::Internal { tag }, None) => json!({
+                            "type": "object",
+                            "properties": { tag: { "const": v.name } },
+                            "required": [tag],
+                        }),
+                        (TagType::Internal { tag }, Some(payload)) => match &v.kind {
+                            VariantKind::Struct(fields) => {
+                                let mut schema = object_schema(fields, ref_prefix);
+                                schema["properties"]
+                                    .as_object_mut()
+                                    .unwrap()
+                                    .insert(tag.clone(), json!({ "const": v.name }));
+                                schema["required"]
+                                    .as_array_mut()
+                                    .unwrap()
+                                    .insert(0, json!(tag));
+                                Value::Object(schema)
+                            }
+                            _ => json!({
+                                "allOf": [
+                                    {
+                                        "type": "object",
+                                        "properties": { tag: { "const": v.name } },
+                                        "required": [tag],
+                                    },
+                                    payload,
+                                ],
+                            }),
+                        },
+                        (TagType::Adjacent { tag, content: _ }, None) => json!({
+                            "type": "object",
+                            "properties": { tag: { "const": v.name } },
+                            "required": [tag],
+                        }),
+                        (TagType::Adjacent { tag, content }, Some(payload)) => json!({
+                            "type": "object",
+                            "properties": { tag: { "const": v.name }, content: payload },
+                            "required": [tag, content],
+                        }),
+                        (TagType::None, None) => json!({ "type": "null" }),
+                        (TagType::None, Some(payload)) => payload,
+                    }
+                });
+                // NOTE: untagged variants may overlap, and serde picks the
+                // first one that matches
+                let key = match e.attr.tag {
+                    TagType::None => "anyOf",
+                    _ => "oneOf",
+                };
+                json!({ key: variants.collect::<Vec<_>>() })
+            }
+        }
+        TypeKind::List(_)
+        | TypeKind::Option(_)
+        | TypeKind::Tuple(_)
+        | TypeKind::Record(_, _)
+        | TypeKind::Any
+        | TypeKind::Param(_)
+        | TypeKind::Builtin(_) => return Ok(None),
+    }))
+}
+
+/// All named types in `tys` keyed by their [`def_name`].
+pub fn defs(
+    tys: impl IntoIterator<Item = DynTapi>,
+    ref_prefix: &str,
+) -> Result<Map<String, Value>, Error> {
+    let mut defs = Map::new();
+    let mut names = HashMap::new();
+    for ty in tys {
+        if let Some(decl) = ty_decl(ty, ref_prefix)? {
+            let name = def_name(ty);
+            if let Some(other) = names.insert(name.clone(), ty) {
+                if other.id() != ty.id() {
+                    return Err(NameCollision {
+                        name,
+                        tys: [other.type_name(), ty.type_name()],
+                    }
+                    .into());
+                }
+            }
+            defs.insert(name, decl);
+        }
+    }
+    Ok(defs)
+}
+
+fn tuple_schema(fields: &[DynTapi], ref_prefix: &str) -> Value {
+    if fields.len() == 1 {
+        ty_schema(fields[0], ref_prefix)
+    } else {
+        json!({
+            "type": "array",
+            "prefixItems": fields.iter().map(|f| ty_schema(*f, ref_prefix)).collect::<Vec<_>>(),
+            "minItems": fields.len(),
+            "maxItems": fields.len(),
+        })
+    }
+}
+
+fn object_schema(fields: &[Field], ref_prefix: &str) -> Map<String, Value> {
+    let mut properties = Map::new();
+    let mut required = Vec::new();
+    let mut all_of = Vec::new();
+    let mut additional_properties = None;
+    for f in fields.iter().filter(|f| !f.attr.skip_serializing) {
+        if f.attr.flatten {
+            match f.ty.kind() {
+                TypeKind::Record(_, v) => additional_properties = Some(ty_schema(v, ref_prefix)),
+                // NOTE: an absent flattened option places no constraints on
+                // the object
+                TypeKind::Option(_) => {}
+                _ => all_of.push(ty_schema(f.ty, ref_prefix)),
+            }
+            continue;
+        }
+        let name = match &f.name {
+            FieldName::Named(n) => n.serialize_name.clone(),
+            FieldName::Index(idx) => idx.to_string(),
+        };
+        // NOTE: fields skipped by a predicate may be left out, as may options
+        if !matches!(f.ty.kind(), TypeKind::Option(_)) && f.attr.skip_serializing_if.is_none() {
+            required.push(json!(name));
+        }
+        properties.insert(name, ty_schema(f.ty, ref_prefix));
+    }
+    let mut schema = Map::new();
+    schema.insert("type".to_string(), json!("object"));
+    schema.insert("properties".to_string(), Value::Object(properties));
+    schema.insert("required".to_string(), Value::Array(required));
+    if let Some(additional_properties) = additional_properties {
+        schema.insert("additionalProperties".to_string(), additional_properties);
+    }
+    if !all_of.is_empty() {
+        schema.insert("allOf".to_string(), Value::Array(all_of));
+    }
+    schema
+}
+
+fn builtin_schema(b: BuiltinTypeKind) -> Value {
+    use BuiltinTypeKind::*;
+
+    let (format, unsigned) = match b {
+        U8 => ("uint8", true),
+        U16 => ("uint16", true),
+        U32 => ("uint32", true),
+        U64 => ("uint64", true),
+        U128 => ("uint128", true),
+        Usize => ("uint", true),
+        I8 => ("int8", false),
+        I16 => ("int16", false),
+        I32 => ("int32", false),
+        I64 => ("int64", false),
+        I128 => ("int128", false),
+        Isize => ("int", false),
+        F32 => return json!({ "type": "number", "format": "float" }),
+        F64 => return json!({ "type": "number", "format": "double" }),
+        Bool => return json!({ "type": "boolean" }),
+        Char => return json!({ "type": "string", "minLength": 1, "maxLength": 1 }),
+        String => return json!({ "type": "string" }),
+        Unit => return json!({ "type": "null" }),
+    };
+    if unsigned {
+        json!({ "type": "integer", "format": format, "minimum": 0 })
+    } else {
+        json!({ "type": "integer", "format": format })
+    }
+}
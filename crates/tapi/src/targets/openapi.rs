@@ -0,0 +1,27 @@
+use serde_json::{Map, Value};
+
+use crate::{DynTapi, Error};
+
+use super::json_schema;
+
+pub const REF_PREFIX: &str = "#/components/schemas/";
+
+/// The key under `components/schemas` for a named type.
+pub fn schema_name(ty: DynTapi) -> String {
+    json_schema::def_name(ty)
+}
+
+/// The schema used when referring to `ty`.
+pub fn ty_schema(ty: DynTapi) -> Value {
+    json_schema::ty_schema(ty, REF_PREFIX)
+}
+
+/// The schema placed under `components/schemas` for named types.
+pub fn ty_decl(ty: DynTapi) -> Result<Option<Value>, Error> {
+    json_schema::ty_decl(ty, REF_PREFIX)
+}
+
+/// All named types in `tys` keyed by their [`schema_name`].
+pub fn schemas(tys: impl IntoIterator<Item = DynTapi>) -> Result<Map<String, Value>, Error> {
+    json_schema::defs(tys, REF_PREFIX)
+}
@@ -10,44 +10,251 @@ export const getApiBase = (options) => options?.apiBase ?? GLOBAL_API_BASE;
  * @returns {string}
  */
 export const setGlobalApiBase = (apiBase) => (GLOBAL_API_BASE = apiBase);
+const PATH_PARAMS = /([:*])(\w+)/g;
+/**
+ * @param {string} path
+ * @param {Record<string, unknown>} params
+ * @returns {string}
+ */
+const encodePath = (path, params) =>
+  path.replace(PATH_PARAMS, (_, kind, name) =>
+    kind == "*"
+      ? String(params[name]).split("/").map(encodeURIComponent).join("/")
+      : encodeURIComponent(String(params[name]))
+  );
+/**
+ * Encodes `query` as key-value pairs: objects, which come from flattened
+ * fields and internally tagged enums, are merged into the top level, arrays
+ * become repeated keys, and `null`s are left out.
+ *
+ * Not all of this is decoded by `serde_urlencoded`, which rejects repeated
+ * keys for sequence fields, loses the tag of data-carrying enums in fields,
+ * and only decodes strings in flattened fields and enums. Query and form
+ * types relying on it are rejected when the client is generated.
+ *
+ * @param {unknown} query
+ * @returns {string}
+ */
+const encodeForm = (query) => {
+  const params = new URLSearchParams();
+  /** @param {[string, unknown][]} entries */
+  const append = (entries) => {
+    for (const [key, value] of entries) {
+      for (const v of Array.isArray(value) ? value : [value]) {
+        if (v === null || v === undefined) continue;
+        if (typeof v == "object") append(Object.entries(v));
+        else params.append(key, String(v));
+      }
+    }
+  };
+  append(Array.isArray(query) ? query : Object.entries(query ?? {}));
+  return params.toString();
+};
+/**
+ * @param {unknown} query
+ * @returns {string}
+ */
+const encodeQuery = (query) => {
+  const encoded = encodeForm(query);
+  return encoded ? `?${encoded}` : "";
+};
+/**
+ * Files are sent as is, objects as JSON and everything else as strings.
+ *
+ * @param {unknown} fields
+ * @returns {FormData}
+ */
+const encodeMultipart = (fields) => {
+  if (fields instanceof FormData) return fields;
+  const form = new FormData();
+  for (const [key, value] of Object.entries(fields ?? {})) {
+    for (const v of Array.isArray(value) ? value : [value]) {
+      if (v === null || v === undefined) continue;
+      if (v instanceof Blob) form.append(key, v);
+      else
+        form.append(key, typeof v == "object" ? JSON.stringify(v) : String(v));
+    }
+  }
+  return form;
+};
 /**
- * @template Req
- * @template Res
+ * @param {RequestType} reqTy
+ * @param {unknown} req
+ * @returns {{ contentType?: string; body?: BodyInit }}
+ */
+const encodeBody = (reqTy, req) => {
+  switch (reqTy) {
+    case "json":
+      return { contentType: "application/json", body: JSON.stringify(req) };
+    case "form":
+      return {
+        contentType: "application/x-www-form-urlencoded",
+        body: encodeForm(req),
+      };
+    // NOTE: `fetch` sets the content type along with the boundary
+    case "multipart":
+      return { body: encodeMultipart(req) };
+    case "bytes":
+      return {
+        contentType: "application/octet-stream",
+        body: /** @type {BodyInit} */ (req),
+      };
+    case "text":
+      return { contentType: "text/plain; charset=utf-8", body: String(req) };
+    default:
+      return {};
+  }
+};
+/**
+ * @param {Response} res
+ * @returns {Promise<unknown>}
+ */
+const decodeError = async (res) =>
+  res.headers.get("Content-Type")?.startsWith("application/json")
+    ? await res.json()
+    : await res.text();
+/**
+ * @param {Response} res
+ * @param {ResponseType} resTy
+ * @returns {Promise<unknown>}
+ */
+const decode = async (res, resTy) => {
+  if (resTy == "none") return "";
+  if (resTy == "json") return await res.json();
+  if (resTy == "text") return await res.text();
+  throw new Error(`Unknown response type ${resTy}`);
+};
+/**
+ * The headers of a request: those of `options`, followed by its header and
+ * cookie params, leaving out any that are `null` or `undefined`.
+ *
+ * NOTE: browsers do not let `fetch` set cookies, so cookie params only take
+ * effect outside of them.
+ *
+ * @param {Record<string, unknown>} params
+ * @param {RequestOptions} reqOptions
+ * @param {ApiOptions} [options]
+ * @returns {Record<string, string>}
+ */
+const encodeHeaders = (params, reqOptions, options) => {
+  /** @type {Record<string, string>} */
+  const headers = { ...options?.headers };
+  /** @param {string[]} [names] */
+  const present = (names) =>
+    (names ?? []).filter(
+      (name) => params[name] !== null && params[name] !== undefined
+    );
+  for (const name of present(reqOptions.headers))
+    headers[name] = String(params[name]);
+  const cookies = present(reqOptions.cookies).map(
+    (name) => `${name}=${String(params[name])}`
+  );
+  if (cookies.length) headers["Cookie"] = cookies.join("; ");
+  return headers;
+};
+/**
+ * Sends the request described by the arguments of a `RequestFn`.
+ *
  * @param {RequestType} reqTy
  * @param {Method} method
  * @param {string} path
- * @param {ResponseType} resTy
- * @returns {(req: Req, options?: ApiOptions) => {    data: Promise<Res>;    abort: () => void;}}
+ * @param {RequestOptions} reqOptions
+ * @param {any[]} args
+ * @returns {{ response: Promise<Response>; abort: () => void }}
  */
-const request = (reqTy, method, path, resTy) => (req, options) => {
+const send = (reqTy, method, path, reqOptions, args) => {
+  const [params, req, options] =
+    path.match(PATH_PARAMS) ||
+    reqOptions.headers?.length ||
+    reqOptions.cookies?.length
+      ? args
+      : [{}, ...args];
   const controller = new AbortController();
+  const abort = () => controller.abort();
   try {
-    const promise = fetch(`${getApiBase(options)}${path}`, {
-      method,
-      headers:
-        reqTy == "json" ? { "Content-Type": "application/json" } : void 0,
-      body: reqTy == "json" ? JSON.stringify(req) : void 0,
-      signal: controller.signal,
-    });
-    return {
-      data: (async () => {
-        const res = await promise;
-        if (!res.ok) throw new Error(await res.text());
-        if (resTy == "none") return "";
-        if (resTy == "json") return await res.json();
-        if (resTy == "text") return await res.text();
-        throw new Error(`Unknown response type ${resTy}`);
-      })(),
-      abort: () => controller.abort(),
-    };
+    const query = reqTy == "query" ? encodeQuery(req) : "";
+    const { contentType, body } = encodeBody(reqTy, req);
+    const headers = encodeHeaders(params, reqOptions, options);
+    if (contentType) headers["Content-Type"] = contentType;
+    const response = fetch(
+      `${getApiBase(options)}${encodePath(path, params)}${query}`,
+      { method, headers, body, signal: controller.signal }
+    );
+    return { response, abort };
   } catch (e) {
     console.error(e);
-    return {
-      data: Promise.reject(e),
-      abort: () => controller.abort(),
-    };
+    return { response: Promise.reject(e), abort };
   }
 };
+/**
+ * @template Req
+ * @template Res
+ * @template [Params=never]
+ * @template [Err=never]
+ * @param {RequestType} reqTy
+ * @param {Method} method
+ * @param {string} path
+ * @param {ResponseType} resTy
+ * @param {RequestOptions} [reqOptions]
+ * @returns {RequestFn<Params, Req, Res, Err>}
+ */
+const request = (reqTy, method, path, resTy, reqOptions = {}) =>
+  /** @type {RequestFn<Params, Req, Res, Err>} */ (
+    /** @type {unknown} */ (
+      /** @param {any[]} args */
+      (...args) => {
+        const { response, abort } = send(reqTy, method, path, reqOptions, args);
+        return {
+          data: (async () => {
+            const res = await response;
+            if (!res.ok) {
+              if (!reqOptions.typedErrors) throw new Error(await res.text());
+              return {
+                ok: false,
+                status: res.status,
+                error: await decodeError(res),
+              };
+            }
+            const data = await decode(res, resTy);
+            return reqOptions.typedErrors ? { ok: true, data } : data;
+          })(),
+          abort,
+        };
+      }
+    )
+  );
+/**
+ * Like `request`, but for endpoints declaring the response of each status
+ * code. Resolves to `{ status, data }`, and throws on undeclared statuses.
+ *
+ * @template Req
+ * @template Res
+ * @template [Params=never]
+ * @param {RequestType} reqTy
+ * @param {Method} method
+ * @param {string} path
+ * @param {Record<number, ResponseType>} resTys
+ * @param {RequestOptions} [reqOptions]
+ * @returns {RequestFn<Params, Req, Res>}
+ */
+const requestStatus = (reqTy, method, path, resTys, reqOptions = {}) =>
+  /** @type {RequestFn<Params, Req, Res>} */ (
+    /** @type {unknown} */ (
+      /** @param {any[]} args */
+      (...args) => {
+        const { response, abort } = send(reqTy, method, path, reqOptions, args);
+        return {
+          data: (async () => {
+            const res = await response;
+            const resTy = resTys[res.status];
+            if (!resTy) throw new Error(await res.text());
+            return { status: res.status, data: await decode(res, resTy) };
+          })(),
+          abort,
+        };
+      }
+    )
+  );
 /**
  * @template T
  * @template P
@@ -86,9 +293,48 @@ const sse = (url, resTy) => (params, options) => {
  * @property {string} [apiBase]
  * @property {Record<string, string>} [headers]
  */
-/** @typedef {"none" | "json"} RequestType */
+/** @typedef {"none" | "json" | "query" | "form" | "multipart" | "bytes" | "text"} RequestType */
+/**
+ * How a request is sent beyond its body, as generated for each endpoint.
+ *
+ * @typedef {Object} RequestOptions
+ * @property {boolean} [typedErrors] Resolve to an `ApiResult` instead of throwing on errors.
+ * @property {string[]} [headers] The params that are sent as headers rather than in the path.
+ * @property {string[]} [cookies] The params that are sent as cookies rather than in the path.
+ */
+/**
+ * The fields of a multipart body, where any of them can be a file.
+ *
+ * @template T
+ * @typedef {{ [K in keyof T]: T[K] | Blob }} MultipartFields
+ */
+/**
+ * The outcome of an endpoint with a typed error, where `error` is the body of
+ * a non-2xx response.
+ *
+ * @template Res
+ * @template Err
+ * @typedef {{ ok: true; data: Res } | { ok: false; status: number; error: Err }} ApiResult
+ */
+/**
+ * @template Res
+ * @template [Err=never]
+ * @typedef {{ data: Promise<[Err] extends [never] ? Res : ApiResult<Res, Err>>; abort: () => void }} RequestResult
+ */
+/**
+ * Endpoints with path, header or cookie parameters take them as their first
+ * argument.
+ *
+ * @template Params
+ * @template Req
+ * @template Res
+ * @template [Err=never]
+ * @typedef {[Params] extends [never]
+ *   ? (req: Req, options?: ApiOptions) => RequestResult<Res, Err>
+ *   : (params: Params, req: Req, options?: ApiOptions) => RequestResult<Res, Err>} RequestFn
+ */
 /** @typedef {"none" | "text" | "json"} ResponseType */
-/** @typedef {"DELETE" | "GET" | "PUT" | "POST" | "HEAD" | "TRACE" | "PATCH"} Method */
+/** @typedef {"DELETE" | "GET" | "PUT" | "POST" | "HEAD" | "OPTIONS" | "TRACE" | "PATCH"} Method */
 /**
  * @typedef {(
  *   event:
@@ -10,39 +10,267 @@ export const getApiBase = (options?: ApiOptions) =>
 export const setGlobalApiBase = (apiBase: string) =>
   (GLOBAL_API_BASE = apiBase);
 
-type RequestType = "none" | "json";
+type RequestType =
+  | "none"
+  | "json"
+  | "query"
+  | "form"
+  | "multipart"
+  | "bytes"
+  | "text";
 type ResponseType = "none" | "text" | "json";
-type Method = "DELETE" | "GET" | "PUT" | "POST" | "HEAD" | "TRACE" | "PATCH";
-const request =
-  <Req, Res>(
-    reqTy: RequestType,
-    method: Method,
-    path: string,
-    resTy: ResponseType
-  ) =>
-  (
-    req: Req,
-    options?: ApiOptions
-  ): { data: Promise<Res>; abort: () => void } => {
-    const controller = new AbortController();
-    const promise = fetch(`${getApiBase(options)}${path}`, {
-      method,
-      headers:
-        reqTy == "json" ? { "Content-Type": "application/json" } : void 0,
-      body: reqTy == "json" ? JSON.stringify(req) : void 0,
-    });
+type Method =
+  | "DELETE"
+  | "GET"
+  | "PUT"
+  | "POST"
+  | "HEAD"
+  | "OPTIONS"
+  | "TRACE"
+  | "PATCH";
+/**
+ * The outcome of an endpoint with a typed error, where `error` is the body of
+ * a non-2xx response.
+ */
+export type ApiResult<Res, Err> =
+  | { ok: true; data: Res }
+  | { ok: false; status: number; error: Err };
+type RequestResult<Res, Err = never> = {
+  data: Promise<[Err] extends [never] ? Res : ApiResult<Res, Err>>;
+  abort: () => void;
+};
+/**
+ * How a request is sent beyond its body, as generated for each endpoint.
+ */
+type RequestOptions = {
+  /** Resolve to an `ApiResult` instead of throwing on errors. */
+  typedErrors?: boolean;
+  /** The params that are sent as headers rather than in the path. */
+  headers?: string[];
+  /** The params that are sent as cookies rather than in the path. */
+  cookies?: string[];
+};
+/**
+ * The fields of a multipart body, where any of them can be a file.
+ */
+export type MultipartFields<T> = { [K in keyof T]: T[K] | Blob };
+/**
+ * Endpoints with path, header or cookie parameters take them as their first
+ * argument.
+ */
+type RequestFn<Params, Req, Res, Err = never> = [Params] extends [never]
+  ? (req: Req, options?: ApiOptions) => RequestResult<Res, Err>
+  : (
+      params: Params,
+      req: Req,
+      options?: ApiOptions
+    ) => RequestResult<Res, Err>;
+
+const PATH_PARAMS = /([:*])(\w+)/g;
+const encodePath = (path: string, params: Record<string, unknown>) =>
+  path.replace(PATH_PARAMS, (_, kind: string, name: string) =>
+    kind == "*"
+      ? String(params[name]).split("/").map(encodeURIComponent).join("/")
+      : encodeURIComponent(String(params[name]))
+  );
+
+/**
+ * Encodes `query` as key-value pairs: objects, which come from flattened
+ * fields and internally tagged enums, are merged into the top level, arrays
+ * become repeated keys, and `null`s are left out.
+ *
+ * Not all of this is decoded by `serde_urlencoded`, which rejects repeated
+ * keys for sequence fields, loses the tag of data-carrying enums in fields,
+ * and only decodes strings in flattened fields and enums. Query and form
+ * types relying on it are rejected when the client is generated.
+ */
+const encodeForm = (query: unknown): string => {
+  const params = new URLSearchParams();
+  const append = (entries: [string, unknown][]) => {
+    for (const [key, value] of entries) {
+      for (const v of Array.isArray(value) ? value : [value]) {
+        if (v === null || v === undefined) continue;
+        if (typeof v == "object") append(Object.entries(v));
+        else params.append(key, String(v));
+      }
+    }
+  };
+  append(
+    Array.isArray(query)
+      ? (query as [string, unknown][])
+      : Object.entries(query ?? {})
+  );
+  return params.toString();
+};
+const encodeQuery = (query: unknown): string => {
+  const encoded = encodeForm(query);
+  return encoded ? `?${encoded}` : "";
+};
+
+/**
+ * Files are sent as is, objects as JSON and everything else as strings.
+ */
+const encodeMultipart = (fields: unknown): FormData => {
+  if (fields instanceof FormData) return fields;
+  const form = new FormData();
+  for (const [key, value] of Object.entries(fields ?? {})) {
+    for (const v of Array.isArray(value) ? value : [value]) {
+      if (v === null || v === undefined) continue;
+      if (v instanceof Blob) form.append(key, v);
+      else
+        form.append(key, typeof v == "object" ? JSON.stringify(v) : String(v));
+    }
+  }
+  return form;
+};
+
+const encodeBody = (
+  reqTy: RequestType,
+  req: unknown
+): { contentType?: string; body?: BodyInit } => {
+  switch (reqTy) {
+    case "json":
+      return { contentType: "application/json", body: JSON.stringify(req) };
+    case "form":
+      return {
+        contentType: "application/x-www-form-urlencoded",
+        body: encodeForm(req),
+      };
+    // NOTE: `fetch` sets the content type along with the boundary
+    case "multipart":
+      return { body: encodeMultipart(req) };
+    case "bytes":
+      return { contentType: "application/octet-stream", body: req as BodyInit };
+    case "text":
+      return { contentType: "text/plain; charset=utf-8", body: String(req) };
+    default:
+      return {};
+  }
+};
+
+const decodeError = async (res: Response): Promise<unknown> =>
+  res.headers.get("Content-Type")?.startsWith("application/json")
+    ? await res.json()
+    : await res.text();
+
+const decode = async (res: Response, resTy: ResponseType): Promise<unknown> => {
+  if (resTy == "none") return "";
+  if (resTy == "json") return await res.json();
+  if (resTy == "text") return await res.text();
+  throw new Error(`Unknown response type ${resTy}`);
+};
+
+/**
+ * The headers of a request: those of `options`, followed by its header and
+ * cookie params, leaving out any that are `null` or `undefined`.
+ *
+ * NOTE: browsers do not let `fetch` set cookies, so cookie params only take
+ * effect outside of them.
+ */
+const encodeHeaders = (
+  params: Record<string, unknown>,
+  reqOptions: RequestOptions,
+  options?: ApiOptions
+): Record<string, string> => {
+  const headers: Record<string, string> = { ...options?.headers };
+  const present = (names?: string[]) =>
+    (names ?? []).filter(
+      (name) => params[name] !== null && params[name] !== undefined
+    );
+  for (const name of present(reqOptions.headers))
+    headers[name] = String(params[name]);
+  const cookies = present(reqOptions.cookies).map(
+    (name) => `${name}=${String(params[name])}`
+  );
+  if (cookies.length) headers["Cookie"] = cookies.join("; ");
+  return headers;
+};
+
+/**
+ * Sends the request described by the arguments of a `RequestFn`.
+ */
+const send = (
+  reqTy: RequestType,
+  method: Method,
+  path: string,
+  reqOptions: RequestOptions,
+  args: any[]
+): { response: Promise<Response>; abort: () => void } => {
+  const [params, req, options]: [
+    Record<string, unknown>,
+    unknown,
+    ApiOptions | undefined
+  ] =
+    path.match(PATH_PARAMS) ||
+    reqOptions.headers?.length ||
+    reqOptions.cookies?.length
+      ? args
+      : [{}, ...args];
+  const controller = new AbortController();
+  const query = reqTy == "query" ? encodeQuery(req) : "";
+  const url = `${getApiBase(options)}${encodePath(path, params)}${query}`;
+  const { contentType, body } = encodeBody(reqTy, req);
+  const headers = encodeHeaders(params, reqOptions, options);
+  if (contentType) headers["Content-Type"] = contentType;
+  const response = fetch(url, {
+    method,
+    headers,
+    body,
+    signal: controller.signal,
+  });
+  return { response, abort: () => controller.abort() };
+};
+
+const request = <Req, Res, Params = never, Err = never>(
+  reqTy: RequestType,
+  method: Method,
+  path: string,
+  resTy: ResponseType,
+  reqOptions: RequestOptions = {}
+): RequestFn<Params, Req, Res, Err> =>
+  ((...args: any[]): RequestResult<Res, Err> => {
+    const { response, abort } = send(reqTy, method, path, reqOptions, args);
+    return {
+      data: (async () => {
+        const res = await response;
+        if (!res.ok) {
+          if (!reqOptions.typedErrors) throw new Error(await res.text());
+          return {
+            ok: false,
+            status: res.status,
+            error: await decodeError(res),
+          };
+        }
+        const data = await decode(res, resTy);
+        return reqOptions.typedErrors ? { ok: true, data } : data;
+      })() as RequestResult<Res, Err>["data"],
+      abort,
+    };
+  }) as RequestFn<Params, Req, Res, Err>;
+
+/**
+ * Like `request`, but for endpoints declaring the response of each status
+ * code. Resolves to `{ status, data }`, and throws on undeclared statuses.
+ */
+const requestStatus = <Req, Res, Params = never>(
+  reqTy: RequestType,
+  method: Method,
+  path: string,
+  resTys: Record<number, ResponseType>,
+  reqOptions: RequestOptions = {}
+): RequestFn<Params, Req, Res> =>
+  ((...args: any[]): RequestResult<Res> => {
+    const { response, abort } = send(reqTy, method, path, reqOptions, args);
     return {
       data: (async () => {
-        const res = await promise;
-        if (!res.ok) throw new Error(await res.text());
-        if (resTy == "none") return "" as Res;
-        if (resTy == "json") return (await res.json()) as Res;
-        if (resTy == "text") return (await res.text()) as Res;
-        throw new Error(`Unknown response type ${resTy}`);
+        const res = await response;
+        const resTy = resTys[res.status];
+        if (!resTy) throw new Error(await res.text());
+        return { status: res.status, data: await decode(res, resTy) } as Res;
       })(),
-      abort: () => controller.abort(),
+      abort,
     };
-  };
+  }) as RequestFn<Params, Req, Res>;
 
 export type SSEStream<T> = (
   event:
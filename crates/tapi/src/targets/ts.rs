@@ -1,9 +1,18 @@
+use std::{
+    any::TypeId,
+    cell::RefCell,
+    collections::{HashMap, HashSet},
+};
+
 use itertools::Itertools;
 
 use crate::{
     builder::TypesBuilder,
-    kind::{BuiltinTypeKind, TagType, TypeKind, VariantKind},
-    DynTapi,
+    error,
+    kind::{
+        BuiltinTypeKind, Default, Field, FieldName, Struct, TagType, TypeKind, VariantKind, View,
+    },
+    DynTapi, Error,
 };
 
 pub fn builder() -> TypesBuilder {
@@ -16,23 +25,52 @@ pub fn builder() -> TypesBuilder {
 }
 
 pub fn full_ty_name(ty: DynTapi) -> String {
-    let mut name = ty_name(ty);
+    full_view_name(ty, View::Output)
+}
+
+/// [`full_ty_name`] as seen from `view`.
+pub fn full_view_name(ty: DynTapi, view: View) -> String {
+    let mut name = view_name(ty, view);
     for p in ty.path().iter().rev() {
         name = format!("{}.{}", p, name);
     }
     name
 }
 
-pub fn ty_name(ty: DynTapi) -> String {
+/// The name a named type is declared under, without any type arguments.
+pub fn decl_name(ty: DynTapi) -> String {
     match ty.kind() {
         TypeKind::Struct(s) => s.attr.name.serialize_name,
         TypeKind::TupleStruct(s) => s.attr.name.serialize_name,
         TypeKind::Enum(e) => e.attr.name.serialize_name,
-        TypeKind::List(ty) => format!("{}[]", full_ty_name(ty)),
-        TypeKind::Option(ty) => format!("({} | null)", full_ty_name(ty)),
-        TypeKind::Tuple(fields) => ts_tuple(&fields),
-        TypeKind::Record(k, v) => format!("Record<{}, {}>", full_ty_name(k), full_ty_name(v)),
+        _ => ty_name(ty),
+    }
+}
+
+pub fn ty_name(ty: DynTapi) -> String {
+    view_name(ty, View::Output)
+}
+
+pub fn view_name(ty: DynTapi, view: View) -> String {
+    match ty.kind() {
+        TypeKind::Struct(_) | TypeKind::TupleStruct(_) | TypeKind::Enum(_) => {
+            let suffix = if view == View::Input && differs(ty.generic_decl()) {
+                "Input"
+            } else {
+                ""
+            };
+            format!("{}{suffix}{}", decl_name(ty), ts_generics(ty, view))
+        }
+        TypeKind::List(ty) => format!("{}[]", full_view_name(ty, view)),
+        TypeKind::Option(ty) => format!("({} | null)", full_view_name(ty, view)),
+        TypeKind::Tuple(fields) => ts_tuple(&fields, view),
+        TypeKind::Record(k, v) => format!(
+            "Record<{}, {}>",
+            full_view_name(k, view),
+            full_view_name(v, view)
+        ),
         TypeKind::Any => "any".to_string(),
+        TypeKind::Param(name) => name,
         TypeKind::Builtin(b) => match b {
             BuiltinTypeKind::U8
             | BuiltinTypeKind::U16
@@ -55,132 +93,407 @@ pub fn ty_name(ty: DynTapi) -> String {
     }
 }
 
-pub fn ty_decl(ty: DynTapi) -> Option<String> {
-    use std::fmt::Write;
-    fn inner(ty: DynTapi) -> Result<Option<String>, std::fmt::Error> {
-        Ok(Some(match ty.kind() {
-            TypeKind::Struct(s) => {
-                if s.attr.transparent {
-                    format!(
-                        "export type {} = {};",
-                        s.attr.name.serialize_name,
-                        ty_name(
-                            s.fields
-                                .iter()
-                                .find(|f| !f.attr.skip_serializing)
-                                .unwrap()
-                                .ty
-                        ),
-                    )
-                } else {
-                    let ts_fields = ts_fields(true, &s.fields);
-                    format!(
-                        "export type {} = {{\n{ts_fields}\n}};",
-                        s.attr.name.serialize_name,
-                    )
+thread_local! {
+    /// The result of [`differs`] for each declaration.
+    static DIFFERS: RefCell<HashMap<TypeId, bool>> = RefCell::default();
+}
+
+/// Whether the declaration `ty` is declared as both `FooInput` and `FooOutput`.
+pub fn differs(ty: DynTapi) -> bool {
+    fn fields_differ(fields: &[Field]) -> bool {
+        fields.iter().any(|f| {
+            let shape =
+                |view| field_shape(f, view).map(|(name, optional, ty)| (name, optional, ty.id()));
+            shape(View::Input) != shape(View::Output) || !aliases(f).is_empty()
+        })
+    }
+    /// Whether the fields of `ty` itself differ.
+    fn fields_of_differ(ty: DynTapi) -> bool {
+        match ty.kind() {
+            TypeKind::Struct(s) => fields_differ(&defaulted_fields(&s)),
+            TypeKind::Enum(e) => e.variants.iter().any(|v| match &v.kind {
+                VariantKind::Struct(fields) => fields_differ(fields),
+                VariantKind::Unit | VariantKind::Tuple(_) => false,
+            }),
+            _ => false,
+        }
+    }
+    /// The declarations `ty` refers to.
+    fn referred(ty: DynTapi, out: &mut Vec<DynTapi>) {
+        match ty.kind() {
+            TypeKind::Struct(_) | TypeKind::TupleStruct(_) | TypeKind::Enum(_) => {
+                out.push(ty.generic_decl());
+                for g in ty.generics() {
+                    referred(g, out);
                 }
             }
-            TypeKind::TupleStruct(s) => {
-                let ts_fields = ts_tuple(&s.fields.iter().map(|f| f.ty).collect_vec());
-                format!("export type {} = {ts_fields};", s.attr.name.serialize_name,)
+            _ => {
+                for ty in ty.dependencies() {
+                    referred(ty, out);
+                }
             }
-            TypeKind::Enum(e) => {
-                let mut out = String::new();
-                write!(out, "export type {} =\n  | ", e.attr.name.serialize_name)?;
-
-                let has_data = e
-                    .variants
-                    .iter()
-                    .any(|v| matches!(&v.kind, VariantKind::Tuple(_) | VariantKind::Struct(_)));
-
-                let variants = e.variants.iter().map(|v| match &v.kind {
-                    VariantKind::Unit => match &e.attr.tag {
-                        TagType::External => format!("{:?}", v.name),
-                        TagType::Internal { tag } | TagType::Adjacent { tag, content: _ } => {
-                            format!("{{ {tag:?}: {:?} }}", v.name)
-                        }
-                        TagType::None => todo!("{}:{}", file!(), line!()),
-                    },
-                    VariantKind::Tuple(fields) => match &e.attr.tag {
-                        TagType::External => {
-                            format!("{{ {:?}: {} }}", v.name, ts_tuple(fields))
-                        }
-                        TagType::Internal { tag: _ } => {
-                            unreachable!("tagged tuples are not allowed by serde")
-                        }
-                        TagType::Adjacent { tag, content } => {
-                            format!(
-                                "{{ {tag:?}: {:?}, {content:?}: {} }}",
-                                v.name,
-                                ts_tuple(fields),
-                            )
-                        }
-                        TagType::None => todo!("{}:{}", file!(), line!()),
-                    },
-                    VariantKind::Struct(fields) => match &e.attr.tag {
-                        TagType::External => {
-                            let ts_fields = ts_fields(false, fields);
-                            format!("{{ {:?}: {{ {ts_fields} }} }}", v.name)
-                        }
-                        TagType::Internal { tag } => {
-                            let ts_fields = ts_fields(false, fields);
-                            format!("{{ {tag:?}: {:?}, {ts_fields} }}", v.name)
-                        }
-                        TagType::Adjacent { tag, content } => {
-                            let ts_fields = ts_fields(false, fields);
-                            format!(
-                                "{{ {tag:?}: {:?}, {content:?}: {{ {ts_fields} }} }}",
+        }
+    }
+
+    let cached = |ty: DynTapi| DIFFERS.with(|d| d.borrow().get(&ty.id()).copied());
+    if let Some(differs) = cached(ty) {
+        return differs;
+    }
+    // NOTE: a declaration differs if any declaration it reaches does, so
+    // when none of them do, none of the visited ones differ either
+    let mut visited = HashSet::new();
+    let mut stack = vec![ty];
+    let mut differs = false;
+    while let Some(decl) = stack.pop() {
+        if !visited.insert(decl.id()) {
+            continue;
+        }
+        match cached(decl) {
+            Some(true) => differs = true,
+            Some(false) => continue,
+            None => differs = fields_of_differ(decl),
+        }
+        if differs {
+            break;
+        }
+        let mut refs = Vec::new();
+        for ty in decl.dependencies() {
+            referred(ty, &mut refs);
+        }
+        stack.extend(refs);
+    }
+    DIFFERS.with(|d| {
+        let mut d = d.borrow_mut();
+        if differs {
+            d.insert(ty.id(), true);
+        } else {
+            d.extend(visited.into_iter().map(|id| (id, false)));
+        }
+    });
+    differs
+}
+
+pub fn ty_decl(ty: DynTapi) -> Result<Option<String>, Error> {
+    let ty = ty.generic_decl();
+    if differs(ty) {
+        let Some(input) = view_decl(ty, View::Input, "Input")? else {
+            return Ok(None);
+        };
+        let Some(output) = view_decl(ty, View::Output, "Output")? else {
+            return Ok(None);
+        };
+        let generics = ts_generics(ty, View::Output);
+        Ok(Some(format!(
+            "{input}\n{output}\n{}export type {} = {}Output{generics};",
+            jsdoc(type_docs(ty).as_deref(), ""),
+            ty_name(ty),
+            decl_name(ty),
+        )))
+    } else {
+        view_decl(ty, View::Output, "")
+    }
+}
+
+/// The declaration of `ty` as seen from `view`, named with `suffix`.
+pub fn view_decl(ty: DynTapi, view: View, suffix: &str) -> Result<Option<String>, Error> {
+    use std::fmt::Write;
+    error::check_fields(ty)?;
+    let name = format!("{}{suffix}{}", decl_name(ty), ts_generics(ty, view));
+    let decl = match ty.kind() {
+        TypeKind::Struct(s) => {
+            if s.attr.transparent {
+                format!(
+                    "export type {name} = {};",
+                    view_name(error::transparent_field(ty, &s.fields, view)?.ty, view),
+                )
+            } else {
+                let fields = defaulted_fields(&s);
+                let ts_fields = ts_fields(true, &fields, view);
+                let ts_flatten = ts_flatten(&fields, view);
+                format!("export type {name} = {{\n{ts_fields}\n}}{ts_flatten};")
+            }
+        }
+        TypeKind::TupleStruct(s) => {
+            let ts_fields = ts_tuple(&s.fields.iter().map(|f| f.ty).collect_vec(), view);
+            format!("export type {name} = {ts_fields};")
+        }
+        TypeKind::Enum(e) => {
+            let mut out = String::new();
+            write!(out, "export type {name} =").unwrap();
+
+            let has_data = e
+                .variants
+                .iter()
+                .any(|v| matches!(&v.kind, VariantKind::Tuple(_) | VariantKind::Struct(_)));
+
+            let variants = e
+                .variants
+                .iter()
+                .map(|v| {
+                    let variant = match &v.kind {
+                        VariantKind::Unit => match &e.attr.tag {
+                            TagType::External => format!("{:?}", v.name),
+                            TagType::Internal { tag } | TagType::Adjacent { tag, content: _ } => {
+                                format!("{{ {tag:?}: {:?} }}", v.name)
+                            }
+                            TagType::None => "null".to_string(),
+                        },
+                        VariantKind::Tuple(fields) => match &e.attr.tag {
+                            TagType::External => {
+                                format!("{{ {:?}: {} }}", v.name, ts_tuple(fields, view))
+                            }
+                            // NOTE: serde only allows newtype variants here
+                            TagType::Internal { tag } => {
+                                format!("{{ {tag:?}: {:?} }} & {}", v.name, ts_tuple(fields, view))
+                            }
+                            TagType::Adjacent { tag, content } => {
+                                format!(
+                                    "{{ {tag:?}: {:?}, {content:?}: {} }}",
+                                    v.name,
+                                    ts_tuple(fields, view),
+                                )
+                            }
+                            TagType::None => ts_tuple(fields, view),
+                        },
+                        VariantKind::Struct(fields) => {
+                            let ts_fields = ts_fields(false, fields, view);
+                            let ts_flatten = ts_flatten(fields, view);
+                            match &e.attr.tag {
+                                TagType::External => {
+                                    format!("{{ {:?}: {{ {ts_fields} }}{ts_flatten} }}", v.name)
+                                }
+                                TagType::Internal { tag } => {
+                                    format!("{{ {tag:?}: {:?}, {ts_fields} }}{ts_flatten}", v.name)
+                                }
+                                TagType::Adjacent { tag, content } => format!(
+                                "{{ {tag:?}: {:?}, {content:?}: {{ {ts_fields} }}{ts_flatten} }}",
                                 v.name
-                            )
+                            ),
+                                TagType::None => format!("{{ {ts_fields} }}{ts_flatten}"),
+                            }
                         }
-                        TagType::None => todo!("TagType::None @ {}:{}", file!(), line!()),
-                    },
-                });
-
-                write!(out, "{};", variants.clone().format("\n  | "))?;
-                if !has_data {
-                    write!(
-                        out,
-                        "\nexport const {}: {}[] = [{}];",
-                        heck::AsShoutySnakeCase(&e.attr.name.serialize_name),
-                        e.attr.name.serialize_name,
-                        variants.format(", "),
-                    )?;
-                }
-                out
+                    };
+                    (v.docs.as_deref(), variant)
+                })
+                // NOTE: untagged unit variants all become `null`
+                .unique_by(|(_, variant)| variant.clone());
+
+            for (docs, variant) in variants.clone() {
+                write!(out, "\n{}  | {variant}", jsdoc(docs, "  ")).unwrap();
             }
-            TypeKind::List(_)
-            | TypeKind::Option(_)
-            | TypeKind::Tuple(_)
-            | TypeKind::Record(_, _)
-            | TypeKind::Any
-            | TypeKind::Builtin(_) => return Ok(None),
-        }))
+            write!(out, ";").unwrap();
+            // NOTE: untagged unit variants all serialize as `null`, so
+            // there is nothing meaningful to enumerate
+            if !has_data && !matches!(e.attr.tag, TagType::None) {
+                write!(
+                    out,
+                    "\nexport const {}: {name}[] = [{}];",
+                    heck::AsShoutySnakeCase(&e.attr.name.serialize_name),
+                    variants.map(|(_, variant)| variant).format(", "),
+                )
+                .unwrap();
+            }
+            out
+        }
+        TypeKind::List(_)
+        | TypeKind::Option(_)
+        | TypeKind::Tuple(_)
+        | TypeKind::Record(_, _)
+        | TypeKind::Any
+        | TypeKind::Param(_)
+        | TypeKind::Builtin(_) => return Ok(None),
+    };
+    Ok(Some(format!(
+        "{}{decl}",
+        jsdoc(type_docs(ty).as_deref(), "")
+    )))
+}
+
+/// The type arguments of a generic instantiation, e.g. `<number, string>`.
+pub fn ts_generics(ty: DynTapi, view: View) -> String {
+    let generics = ty.generics();
+    if generics.is_empty() {
+        String::new()
+    } else {
+        format!(
+            "<{}>",
+            generics
+                .iter()
+                .map(|g| full_view_name(*g, view))
+                .format(", ")
+        )
     }
-    inner(ty).unwrap()
 }
 
-pub fn ts_tuple(fields: &[DynTapi]) -> String {
+pub fn ts_tuple(fields: &[DynTapi], view: View) -> String {
     if fields.len() == 1 {
-        format!("{}", fields.iter().map(|f| full_ty_name(*f)).format(", "))
+        full_view_name(fields[0], view)
     } else {
-        format!("[{}]", fields.iter().map(|f| full_ty_name(*f)).format(", "))
+        format!(
+            "[{}]",
+            fields.iter().map(|f| full_view_name(*f, view)).format(", ")
+        )
     }
 }
 
-pub fn ts_fields(multi_line: bool, fields: &[crate::kind::Field]) -> impl std::fmt::Display + '_ {
-    let fields = fields.iter().filter(|f| !f.attr.skip_serializing).map(|f| {
-        let name = match &f.name {
-            crate::kind::FieldName::Named(n) => &n.serialize_name,
-            crate::kind::FieldName::Index(_) => todo!(),
-        };
-        (name, full_ty_name(f.ty))
-    });
+pub fn ts_fields(multi_line: bool, fields: &[Field], view: View) -> String {
+    let fields = fields
+        .iter()
+        // NOTE: aliased fields are rendered by `ts_flatten` when deserializing
+        .filter(|f| !f.attr.flatten && (view == View::Output || aliases(f).is_empty()))
+        .filter_map(|f| Some((f.docs.as_deref(), ts_field(f, view)?)));
     if multi_line {
         fields
-            .map(|(name, ty)| format!("  {name}: {ty}"))
+            .map(|(docs, (name, ty))| format!("{}  {name}: {ty}", jsdoc(docs, "  ")))
             .join(",\n")
     } else {
-        fields.map(|(name, ty)| format!("{name}: {ty}")).join(", ")
+        fields
+            .map(|(docs, (name, ty))| format!("{}{name}: {ty}", jsdoc_inline(docs)))
+            .join(", ")
+    }
+}
+
+/// The doc comment of a named type.
+pub fn type_docs(ty: DynTapi) -> Option<String> {
+    match ty.kind() {
+        TypeKind::Struct(s) => s.docs,
+        TypeKind::TupleStruct(s) => s.docs,
+        TypeKind::Enum(e) => e.docs,
+        _ => None,
+    }
+}
+
+/// `docs` as a JSDoc comment on its own lines, each prefixed by `indent`.
+pub fn jsdoc(docs: Option<&str>, indent: &str) -> String {
+    let Some(docs) = docs else {
+        return String::new();
+    };
+    let docs = docs.replace("*/", "*\\/");
+    if docs.lines().count() <= 1 {
+        format!("{indent}/** {docs} */\n")
+    } else {
+        let lines = docs
+            .lines()
+            .map(|l| format!("{indent} * {l}").trim_end().to_string())
+            .format("\n");
+        format!("{indent}/**\n{lines}\n{indent} */\n")
+    }
+}
+
+/// `docs` as a JSDoc comment placed in front of an inline property.
+fn jsdoc_inline(docs: Option<&str>) -> String {
+    match docs {
+        Some(docs) => format!(
+            "/** {} */ ",
+            docs.replace("*/", "*\\/").lines().map(str::trim).join(" ")
+        ),
+        None => String::new(),
+    }
+}
+
+/// The property name and type of a field in `view`.
+pub fn ts_field(f: &Field, view: View) -> Option<(String, String)> {
+    let (name, optional, ty) = field_shape(f, view)?;
+    let name = property_name(&name);
+    let name = if optional { format!("{name}?") } else { name };
+    Some((name, full_view_name(ty, view)))
+}
+
+/// Whether `name` can be written as is, e.g. as a property name.
+pub fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_alphabetic() || c == '_' || c == '$')
+        && chars.all(|c| c.is_alphanumeric() || c == '_' || c == '$')
+}
+
+/// `name` as an object property, quoted unless it is an identifier.
+pub fn property_name(name: &str) -> String {
+    if is_identifier(name) {
+        name.to_string()
+    } else {
+        format!("{name:?}")
+    }
+}
+
+/// The name of a field in `view`, whether it may be left out, and its type.
+pub fn field_shape(f: &Field, view: View) -> Option<(String, bool, DynTapi)> {
+    if f.attr.skipped(view) {
+        return None;
+    }
+    let name = match &f.name {
+        FieldName::Named(n) => n.get(view).to_string(),
+        // NOTE: rejected by `check_fields` once the type is declared
+        FieldName::Index(idx) => idx.to_string(),
+    };
+    let defaulted = !matches!(f.attr.default, Default::None);
+    Some(match (view, f.ty.kind()) {
+        (View::Input, TypeKind::Option(_)) => (name, true, f.ty),
+        (View::Input, _) => (name, defaulted, f.ty),
+        (View::Output, TypeKind::Option(ty))
+            if f.attr
+                .skip_serializing_if
+                .as_ref()
+                .is_some_and(|p| p.ends_with("Option::is_none")) =>
+        {
+            (name, true, ty)
+        }
+        (View::Output, _) => (name, f.attr.skip_serializing_if.is_some(), f.ty),
+    })
+}
+
+/// The names a field is accepted under besides its deserialize name.
+fn aliases(f: &Field) -> Vec<&str> {
+    match &f.name {
+        FieldName::Named(n) => f
+            .attr
+            .aliases
+            .iter()
+            .filter(|a| **a != n.deserialize_name)
+            .map(|a| a.as_str())
+            .collect(),
+        FieldName::Index(_) => Vec::new(),
+    }
+}
+
+/// The fields of `s`, defaulted if the container is.
+pub fn defaulted_fields(s: &Struct) -> Vec<Field> {
+    let mut fields = s.fields.clone();
+    if !matches!(s.attr.default, Default::None) {
+        for f in &mut fields {
+            if matches!(f.attr.default, Default::None) {
+                f.attr.default = Default::Default;
+            }
+        }
+    }
+    fields
+}
+
+/// The intersections contributed by `#[serde(flatten)]` fields and aliases.
+pub fn ts_flatten(fields: &[Field], view: View) -> String {
+    let flattened = fields
+        .iter()
+        .filter(|f| !f.attr.skipped(view) && f.attr.flatten)
+        .map(|f| format!(" & {}", ts_flattened_ty(f.ty, view)));
+    let aliased = fields
+        .iter()
+        .filter(|f| view == View::Input && !f.attr.flatten && !aliases(f).is_empty())
+        .filter_map(|f| {
+            let (name, ty) = ts_field(f, view)?;
+            let optional = if name.ends_with('?') { "?" } else { "" };
+            let names = std::iter::once(name.trim_end_matches('?').to_string())
+                .chain(aliases(f).into_iter().map(property_name))
+                .map(|name| format!("{{ {name}{optional}: {ty} }}"));
+            Some(format!(" & ({})", names.format(" | ")))
+        });
+    flattened.chain(aliased).collect()
+}
+
+fn ts_flattened_ty(ty: DynTapi, view: View) -> String {
+    match ty.kind() {
+        TypeKind::Record(_, v) => format!("{{ [key: string]: {} }}", full_view_name(v, view)),
+        TypeKind::Option(ty) => format!("Partial<{}>", ts_flattened_ty(ty, view)),
+        _ => full_view_name(ty, view),
     }
 }
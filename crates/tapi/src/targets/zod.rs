@@ -0,0 +1,293 @@
+use itertools::Itertools;
+
+use crate::{
+    builder::TypesBuilder,
+    error,
+    kind::{BuiltinTypeKind, TagType, TypeKind, VariantKind, View},
+    transitive_closure, DynTapi, Error,
+};
+
+pub fn builder() -> TypesBuilder {
+    TypesBuilder {
+        prelude: "import { z } from \"zod\";\n".to_string(),
+        start_namespace: Box::new(|_, name| format!("export namespace {} {{", name)),
+        end_namespace: Box::new(|_, _| "}".to_string()),
+        decl: Box::new(ty_decl),
+    }
+}
+
+/// The schema constant of a named type, e.g. `PersonSchema`.
+pub fn schema_name(ty: DynTapi) -> String {
+    format!("{}Schema", super::ts::decl_name(ty))
+}
+
+pub fn full_schema_name(ty: DynTapi) -> String {
+    let mut name = schema_name(ty);
+    for p in ty.path().iter().rev() {
+        name = format!("{}.{}", p, name);
+    }
+    name
+}
+
+/// The zod expression validating `ty`.
+pub fn zod_ty(ty: DynTapi) -> String {
+    match ty.kind() {
+        TypeKind::Struct(_) | TypeKind::TupleStruct(_) | TypeKind::Enum(_) => {
+            let generics = ty.generics();
+            if generics.is_empty() {
+                format!("z.lazy(() => {})", full_schema_name(ty))
+            } else {
+                format!(
+                    "z.lazy(() => {}({}))",
+                    full_schema_name(ty),
+                    generics.iter().map(|g| zod_ty(*g)).format(", ")
+                )
+            }
+        }
+        TypeKind::List(ty) => format!("z.array({})", zod_ty(ty)),
+        TypeKind::Option(ty) => format!("z.nullable({})", zod_ty(ty)),
+        TypeKind::Tuple(fields) => zod_tuple(&fields),
+        TypeKind::Record(k, v) => {
+            let key = match k.kind() {
+                TypeKind::Enum(_)
+                | TypeKind::Builtin(BuiltinTypeKind::String | BuiltinTypeKind::Char) => zod_ty(k),
+                _ => "z.string()".to_string(),
+            };
+            format!("z.record({key}, {})", zod_ty(v))
+        }
+        TypeKind::Any => "z.any()".to_string(),
+        TypeKind::Param(name) => name,
+        TypeKind::Builtin(b) => match b {
+            BuiltinTypeKind::U8
+            | BuiltinTypeKind::U16
+            | BuiltinTypeKind::U32
+            | BuiltinTypeKind::U64
+            | BuiltinTypeKind::U128
+            | BuiltinTypeKind::I8
+            | BuiltinTypeKind::I16
+            | BuiltinTypeKind::I32
+            | BuiltinTypeKind::I64
+            | BuiltinTypeKind::I128
+            | BuiltinTypeKind::F32
+            | BuiltinTypeKind::F64
+            | BuiltinTypeKind::Usize
+            | BuiltinTypeKind::Isize => "z.number()".to_string(),
+            BuiltinTypeKind::Bool => "z.boolean()".to_string(),
+            BuiltinTypeKind::Char | BuiltinTypeKind::String => "z.string()".to_string(),
+            BuiltinTypeKind::Unit => "z.null()".to_string(),
+        },
+    }
+}
+
+pub fn ty_decl(ty: DynTapi) -> Result<Option<String>, Error> {
+    let ty = ty.generic_decl();
+    error::check_fields(ty)?;
+    let schema = match ty.kind() {
+        TypeKind::Struct(s) => {
+            if s.attr.transparent {
+                zod_ty(error::transparent_field(ty, &s.fields, View::Output)?.ty)
+            } else {
+                let zod_fields = zod_fields(true, &s.fields);
+                let zod_flatten = zod_flatten(&s.fields);
+                format!("z.object({{\n{zod_fields}\n}}){zod_flatten}")
+            }
+        }
+        TypeKind::TupleStruct(s) => zod_tuple(&s.fields.iter().map(|f| f.ty).collect_vec()),
+        TypeKind::Enum(e) => {
+            let is_unit = e
+                .variants
+                .iter()
+                .all(|v| matches!(v.kind, VariantKind::Unit));
+
+            if e.variants.is_empty() {
+                "z.never()".to_string()
+            } else if is_unit && matches!(e.attr.tag, TagType::External) {
+                format!(
+                    "z.enum([{}])",
+                    e.variants
+                        .iter()
+                        .map(|v| format!("{:?}", v.name))
+                        .format(", ")
+                )
+            } else {
+                let variants = e
+                    .variants
+                    .iter()
+                    .map(|v| match &v.kind {
+                        VariantKind::Unit => match &e.attr.tag {
+                            TagType::External => format!("z.literal({:?})", v.name),
+                            TagType::Internal { tag } | TagType::Adjacent { tag, content: _ } => {
+                                format!("z.object({{ {tag:?}: z.literal({:?}) }})", v.name)
+                            }
+                            TagType::None => "z.null()".to_string(),
+                        },
+                        VariantKind::Tuple(fields) => match &e.attr.tag {
+                            TagType::External => {
+                                format!("z.object({{ {:?}: {} }})", v.name, zod_tuple(fields))
+                            }
+                            TagType::Internal { tag } => format!(
+                                "z.intersection(z.object({{ {tag:?}: z.literal({:?}) }}), {})",
+                                v.name,
+                                zod_tuple(fields),
+                            ),
+                            TagType::Adjacent { tag, content } => format!(
+                                "z.object({{ {tag:?}: z.literal({:?}), {content:?}: {} }})",
+                                v.name,
+                                zod_tuple(fields),
+                            ),
+                            TagType::None => zod_tuple(fields),
+                        },
+                        VariantKind::Struct(fields) => {
+                            let zod_fields = zod_fields(false, fields);
+                            let zod_flatten = zod_flatten(fields);
+                            match &e.attr.tag {
+                                TagType::External => format!(
+                                    "z.object({{ {:?}: z.object({{ {zod_fields} }}){zod_flatten} }})",
+                                    v.name
+                                ),
+                                TagType::Internal { tag } => format!(
+                                    "z.object({{ {tag:?}: z.literal({:?}), {zod_fields} }}){zod_flatten}",
+                                    v.name
+                                ),
+                                TagType::Adjacent { tag, content } => format!(
+                                    "z.object({{ {tag:?}: z.literal({:?}), {content:?}: z.object({{ {zod_fields} }}){zod_flatten} }})",
+                                    v.name
+                                ),
+                                TagType::None => format!("z.object({{ {zod_fields} }}){zod_flatten}"),
+                            }
+                        }
+                    })
+                    .collect_vec();
+
+                // NOTE: `z.discriminatedUnion` only accepts plain objects, so
+                // internally tagged newtype variants and variants with
+                // flattened fields fall back to `z.union`
+                let has_flatten = e.variants.iter().any(|v| match &v.kind {
+                    VariantKind::Struct(fields) => fields.iter().any(|f| f.attr.flatten),
+                    _ => false,
+                });
+                let discriminator = match &e.attr.tag {
+                    TagType::Internal { tag } => e
+                        .variants
+                        .iter()
+                        .all(|v| !matches!(v.kind, VariantKind::Tuple(_)) && !has_flatten)
+                        .then_some(tag),
+                    TagType::Adjacent { tag, content: _ } => (!has_flatten).then_some(tag),
+                    TagType::External | TagType::None => None,
+                };
+
+                if variants.len() == 1 {
+                    variants.into_iter().next().unwrap()
+                } else if let Some(tag) = discriminator {
+                    format!(
+                        "z.discriminatedUnion({tag:?}, [\n  {},\n])",
+                        variants.iter().format(",\n  ")
+                    )
+                } else {
+                    format!("z.union([\n  {},\n])", variants.iter().format(",\n  "))
+                }
+            }
+        }
+        TypeKind::List(_)
+        | TypeKind::Option(_)
+        | TypeKind::Tuple(_)
+        | TypeKind::Record(_, _)
+        | TypeKind::Any
+        | TypeKind::Param(_)
+        | TypeKind::Builtin(_) => return Ok(None),
+    };
+    let name = schema_name(ty);
+    let generics = ty.generics();
+    let params = generics
+        .iter()
+        .map(|g| super::ts::ty_name(*g))
+        .collect_vec();
+    // NOTE: TypeScript cannot infer the type of a schema that refers to
+    // itself, so recursive types are declared up front and the schema is
+    // annotated with them
+    if recursive(ty) {
+        let Some(decl) = super::ts::view_decl(ty, View::Output, "")? else {
+            return Ok(None);
+        };
+        let decl_name = super::ts::decl_name(ty);
+        return Ok(Some(if generics.is_empty() {
+            format!("{decl}\nexport const {name}: z.ZodType<{decl_name}> = {schema};")
+        } else {
+            format!(
+                "{decl}\nexport const {name} = <{}>({}): z.ZodType<{decl_name}<{}>> => {schema};",
+                params
+                    .iter()
+                    .map(|p| format!("{p} extends z.ZodTypeAny"))
+                    .format(", "),
+                params.iter().map(|p| format!("{p}: {p}")).format(", "),
+                params.iter().map(|p| format!("z.infer<{p}>")).format(", "),
+            )
+        }));
+    }
+    if generics.is_empty() {
+        Ok(Some(format!(
+            "export const {name} = {schema};\nexport type {} = z.infer<typeof {name}>;",
+            super::ts::ty_name(ty),
+        )))
+    } else {
+        Ok(Some(format!(
+            "export const {name} = <{}>({}) => {schema};\nexport type {} = z.infer<ReturnType<typeof {name}<{}>>>;",
+            params.iter().map(|p| format!("{p} extends z.ZodTypeAny")).format(", "),
+            params.iter().map(|p| format!("{p}: {p}")).format(", "),
+            super::ts::ty_name(ty),
+            params.iter().map(|p| format!("z.ZodType<{p}>")).format(", "),
+        )))
+    }
+}
+
+/// Whether the declaration `ty` refers back to itself.
+fn recursive(ty: DynTapi) -> bool {
+    transitive_closure(ty.dependencies())
+        .into_iter()
+        .any(|dep| dep.generic_decl().id() == ty.id())
+}
+
+pub fn zod_tuple(fields: &[DynTapi]) -> String {
+    if fields.len() == 1 {
+        zod_ty(fields[0])
+    } else {
+        format!(
+            "z.tuple([{}])",
+            fields.iter().map(|f| zod_ty(*f)).format(", ")
+        )
+    }
+}
+
+pub fn zod_fields(multi_line: bool, fields: &[crate::kind::Field]) -> String {
+    let fields = fields
+        .iter()
+        .filter(|f| !f.attr.flatten)
+        .filter_map(|f| super::ts::field_shape(f, View::Output))
+        .map(|(name, optional, ty)| {
+            let optional = if optional { ".optional()" } else { "" };
+            (
+                super::ts::property_name(&name),
+                format!("{}{optional}", zod_ty(ty)),
+            )
+        });
+    if multi_line {
+        fields
+            .map(|(name, ty)| format!("  {name}: {ty},"))
+            .join("\n")
+    } else {
+        fields.map(|(name, ty)| format!("{name}: {ty}")).join(", ")
+    }
+}
+
+/// The methods chained onto a `z.object(...)` for `#[serde(flatten)]` fields.
+pub fn zod_flatten(fields: &[crate::kind::Field]) -> String {
+    fields
+        .iter()
+        .filter(|f| !f.attr.skip_serializing && f.attr.flatten)
+        .map(|f| match f.ty.kind() {
+            TypeKind::Record(_, v) => format!(".catchall({})", zod_ty(v)),
+            TypeKind::Option(_) => ".passthrough()".to_string(),
+            _ => format!(".and({})", zod_ty(f.ty)),
+        })
+        .collect()
+}
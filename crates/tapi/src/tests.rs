@@ -3,8 +3,8 @@
 use serde::Serialize;
 
 use crate::{
-    targets::{fs, ts},
-    Tapi,
+    targets::{fs, js, json_schema, openapi, ts, zod},
+    DynTapi, Tapi,
 };
 
 #[test]
@@ -17,13 +17,13 @@ fn basic_struct() {
         b: String,
     }
 
-    insta::assert_display_snapshot!(ts::ty_decl(A::boxed()).unwrap_or_default(), @r###"
+    insta::assert_snapshot!(ts::ty_decl(A::boxed()).unwrap().unwrap_or_default(), @r###"
     export type A = {
       a: number,
       b: string
     };
     "###);
-    insta::assert_display_snapshot!(fs::ty_decl(A::boxed()).unwrap_or_default(), @r###"
+    insta::assert_snapshot!(fs::ty_decl(A::boxed()).unwrap().unwrap_or_default(), @r###"
     type A =
       { a: int32
         b: string }
@@ -41,19 +41,71 @@ fn rename_all_struct() {
         field_b: String,
     }
 
-    insta::assert_display_snapshot!(ts::ty_decl(A::boxed()).unwrap_or_default(), @r###"
+    insta::assert_snapshot!(ts::ty_decl(A::boxed()).unwrap().unwrap_or_default(), @r###"
     export type A = {
       fieldA: number,
       fieldB: string
     };
     "###);
-    insta::assert_display_snapshot!(fs::ty_decl(A::boxed()).unwrap_or_default(), @r###"
+    insta::assert_snapshot!(fs::ty_decl(A::boxed()).unwrap().unwrap_or_default(), @r###"
     type A =
       { fieldA: int32
         fieldB: string }
     "###);
 }
 
+#[test]
+fn kebab_case_struct() {
+    #[derive(Tapi)]
+    #[tapi(krate = "crate")]
+    #[serde(rename_all = "kebab-case")]
+    struct A {
+        /// The first field.
+        field_a: i32,
+        #[serde(skip_serializing_if = "Option::is_none", alias = "old-b")]
+        field_b: Option<String>,
+        c: bool,
+    }
+
+    insta::assert_snapshot!(ts::ty_decl(A::boxed()).unwrap().unwrap_or_default(), @r###"
+    export type AInput = {
+      /** The first field. */
+      "field-a": number,
+      c: boolean
+    } & ({ "field-b"?: (string | null) } | { "old-b"?: (string | null) });
+    export type AOutput = {
+      /** The first field. */
+      "field-a": number,
+      "field-b"?: string,
+      c: boolean
+    };
+    export type A = AOutput;
+    "###);
+    insta::assert_snapshot!(js::ty_decl(A::boxed()).unwrap().unwrap_or_default(), @r###"
+    /**
+     * @typedef {{ "field-a": number, c: boolean } & ({ "field-b"?: (string | null) } | { "old-b"?: (string | null) })} tapi.tests.kebab_case_struct.AInput */
+    /**
+     * @typedef {{ "field-a": number, "field-b"?: string, c: boolean }} tapi.tests.kebab_case_struct.AOutput */
+    /**
+     * @typedef {tapi.tests.kebab_case_struct.AOutput} tapi.tests.kebab_case_struct.A */
+    "###);
+    insta::assert_snapshot!(zod::ty_decl(A::boxed()).unwrap().unwrap_or_default(), @r###"
+    export const ASchema = z.object({
+      "field-a": z.number(),
+      "field-b": z.string().optional(),
+      c: z.boolean(),
+    });
+    export type A = z.infer<typeof ASchema>;
+    "###);
+    insta::assert_snapshot!(fs::ty_decl(A::boxed()).unwrap().unwrap_or_default(), @r###"
+    type A =
+      { /// The first field.
+        ``field-a``: int32
+        ``field-b``: Option<string>
+        c: bool }
+    "###);
+}
+
 #[test]
 fn empty_struct() {
     // let _ = color_eyre::install();
@@ -61,12 +113,12 @@ fn empty_struct() {
     #[tapi(krate = "crate")]
     struct A {}
 
-    insta::assert_display_snapshot!(ts::ty_decl(A::boxed()).unwrap_or_default(), @r###"
+    insta::assert_snapshot!(ts::ty_decl(A::boxed()).unwrap().unwrap_or_default(), @r###"
     export type A = {
 
     };
     "###);
-    insta::assert_display_snapshot!(fs::ty_decl(A::boxed()).unwrap_or_default(), @r###"
+    insta::assert_snapshot!(fs::ty_decl(A::boxed()).unwrap().unwrap_or_default(), @r###"
     type A =
       {  }
     "###);
@@ -81,8 +133,8 @@ fn transparent_struct() {
         x: Vec<i32>,
     }
 
-    insta::assert_display_snapshot!(ts::ty_decl(A::boxed()).unwrap_or_default(), @"export type A = number[];");
-    insta::assert_display_snapshot!(fs::ty_decl(A::boxed()).unwrap_or_default(), @r###"
+    insta::assert_snapshot!(ts::ty_decl(A::boxed()).unwrap().unwrap_or_default(), @"export type A = number[];");
+    insta::assert_snapshot!(fs::ty_decl(A::boxed()).unwrap().unwrap_or_default(), @r###"
     type A =
       { x: List<int32> }
     "###);
@@ -94,8 +146,8 @@ fn tuple_single_struct() {
     #[tapi(krate = "crate")]
     struct A(String);
 
-    insta::assert_display_snapshot!(ts::ty_decl(A::boxed()).unwrap_or_default(), @"export type A = string;");
-    insta::assert_display_snapshot!(fs::ty_decl(A::boxed()).unwrap_or_default(), @"type A = string");
+    insta::assert_snapshot!(ts::ty_decl(A::boxed()).unwrap().unwrap_or_default(), @"export type A = string;");
+    insta::assert_snapshot!(fs::ty_decl(A::boxed()).unwrap().unwrap_or_default(), @"type A = string");
 }
 #[test]
 fn tuple_multi_struct() {
@@ -104,8 +156,8 @@ fn tuple_multi_struct() {
     #[tapi(krate = "crate")]
     struct A(String, i32, Vec<A>);
 
-    insta::assert_display_snapshot!(ts::ty_decl(A::boxed()).unwrap_or_default(), @"export type A = [string, number, tapi.tests.tuple_multi_struct.A[]];");
-    insta::assert_display_snapshot!(fs::ty_decl(A::boxed()).unwrap_or_default(), @"type A = string * int32 * List<tapi.tests.tuple_multi_struct.A>");
+    insta::assert_snapshot!(ts::ty_decl(A::boxed()).unwrap().unwrap_or_default(), @"export type A = [string, number, tapi.tests.tuple_multi_struct.A[]];");
+    insta::assert_snapshot!(fs::ty_decl(A::boxed()).unwrap().unwrap_or_default(), @"type A = string * int32 * List<tapi.tests.tuple_multi_struct.A>");
 }
 
 #[test]
@@ -120,8 +172,8 @@ fn transparent_struct_with_multiple_fields() {
         y: String,
     }
 
-    insta::assert_display_snapshot!(ts::ty_decl(A::boxed()).unwrap_or_default(), @"export type A = string;");
-    insta::assert_display_snapshot!(fs::ty_decl(A::boxed()).unwrap_or_default(), @r###"
+    insta::assert_snapshot!(ts::ty_decl(A::boxed()).unwrap().unwrap_or_default(), @"export type A = string;");
+    insta::assert_snapshot!(fs::ty_decl(A::boxed()).unwrap().unwrap_or_default(), @r###"
     type A =
       { y: string }
     "###);
@@ -138,7 +190,7 @@ fn basic_enum() {
         Z,
     }
 
-    insta::assert_display_snapshot!(ts::ty_decl(A::boxed()).unwrap_or_default(), @r###"
+    insta::assert_snapshot!(ts::ty_decl(A::boxed()).unwrap().unwrap_or_default(), @r###"
     export type A =
       | "X"
       | "Y"
@@ -159,13 +211,13 @@ fn tagged_enum() {
         Z,
     }
 
-    insta::assert_display_snapshot!(serde_json::to_string_pretty(&A::X).unwrap(), @r###"
+    insta::assert_snapshot!(serde_json::to_string_pretty(&A::X).unwrap(), @r###"
     {
       "type": "X"
     }
     "###);
 
-    insta::assert_display_snapshot!(ts::ty_decl(A::boxed()).unwrap_or_default(), @r###"
+    insta::assert_snapshot!(ts::ty_decl(A::boxed()).unwrap().unwrap_or_default(), @r###"
     export type A =
       | { "type": "X" }
       | { "type": "Y" }
@@ -195,7 +247,7 @@ fn tagged_enum_with_data() {
         },
         A::Z,
     ];
-    insta::assert_display_snapshot!(serde_json::to_string_pretty(&sample).unwrap(), @r###"
+    insta::assert_snapshot!(serde_json::to_string_pretty(&sample).unwrap(), @r###"
     [
       {
         "type": "X",
@@ -211,13 +263,13 @@ fn tagged_enum_with_data() {
     ]
     "###);
 
-    insta::assert_display_snapshot!(ts::ty_decl(A::boxed()).unwrap_or_default(), @r###"
+    insta::assert_snapshot!(ts::ty_decl(A::boxed()).unwrap().unwrap_or_default(), @r###"
     export type A =
       | { "type": "X", wow: string }
       | { "type": "Y", thingy: string }
       | { "type": "Z" };
     "###);
-    insta::assert_display_snapshot!(fs::ty_decl(A::boxed()).unwrap_or_default(), @r###"
+    insta::assert_snapshot!(fs::ty_decl(A::boxed()).unwrap().unwrap_or_default(), @r###"
     [<JsonFSharpConverter(BaseUnionEncoding = JsonUnionEncoding.UnwrapSingleFieldCases, UnionTagName = "type")>]
     type A =
       | X of wow: string
@@ -245,7 +297,7 @@ fn externally_tagged_with_data() {
         A::Z,
         A::W(1, 2),
     ];
-    insta::assert_display_snapshot!(serde_json::to_string_pretty(&sample).unwrap(), @r###"
+    insta::assert_snapshot!(serde_json::to_string_pretty(&sample).unwrap(), @r###"
     [
       {
         "X": "..."
@@ -265,14 +317,14 @@ fn externally_tagged_with_data() {
     ]
     "###);
 
-    insta::assert_display_snapshot!(ts::ty_decl(A::boxed()).unwrap_or_default(), @r###"
+    insta::assert_snapshot!(ts::ty_decl(A::boxed()).unwrap().unwrap_or_default(), @r###"
     export type A =
       | { "X": string }
       | { "Y": { thingy: string } }
       | "Z"
       | { "W": [number, number] };
     "###);
-    insta::assert_display_snapshot!(fs::ty_decl(A::boxed()).unwrap_or_default(), @r###"
+    insta::assert_snapshot!(fs::ty_decl(A::boxed()).unwrap().unwrap_or_default(), @r###"
     [<JsonFSharpConverter(BaseUnionEncoding = JsonUnionEncoding.ExternalTag + JsonUnionEncoding.UnwrapFieldlessTags + JsonUnionEncoding.UnwrapSingleFieldCases)>]
     type A =
       | X of string
@@ -303,7 +355,7 @@ fn adjacent_with_data() {
         A::Z,
         A::W(1, 2),
     ];
-    insta::assert_display_snapshot!(serde_json::to_string_pretty(&sample).unwrap(), @r###"
+    insta::assert_snapshot!(serde_json::to_string_pretty(&sample).unwrap(), @r###"
     [
       {
         "type": "X",
@@ -328,14 +380,14 @@ fn adjacent_with_data() {
     ]
     "###);
 
-    insta::assert_display_snapshot!(ts::ty_decl(A::boxed()).unwrap_or_default(), @r###"
+    insta::assert_snapshot!(ts::ty_decl(A::boxed()).unwrap().unwrap_or_default(), @r###"
     export type A =
       | { "type": "X", "data": string }
       | { "type": "Y", "data": { thingy: string } }
       | { "type": "Z" }
       | { "type": "W", "data": [number, number] };
     "###);
-    insta::assert_display_snapshot!(fs::ty_decl(A::boxed()).unwrap_or_default(), @r###"
+    insta::assert_snapshot!(fs::ty_decl(A::boxed()).unwrap().unwrap_or_default(), @r###"
     [<JsonFSharpConverter(BaseUnionEncoding = JsonUnionEncoding.UnwrapSingleFieldCases, UnionTagName = "type", UnionFieldsName = "data")>]
     type A =
       | X of string
@@ -345,6 +397,1228 @@ fn adjacent_with_data() {
     "###);
 }
 
+#[test]
+fn untagged_enum() {
+    // let _ = color_eyre::install();
+    #[derive(Tapi, Serialize)]
+    #[tapi(krate = "crate")]
+    #[serde(untagged)]
+    enum A {
+        X(String),
+        Y { thingy: String },
+        Z,
+        W(i32, i32),
+    }
+
+    let sample = [
+        A::X("...".to_string()),
+        A::Y {
+            thingy: "123".to_string(),
+        },
+        A::Z,
+        A::W(1, 2),
+    ];
+    insta::assert_snapshot!(serde_json::to_string_pretty(&sample).unwrap(), @r###"
+    [
+      "...",
+      {
+        "thingy": "123"
+      },
+      null,
+      [
+        1,
+        2
+      ]
+    ]
+    "###);
+
+    insta::assert_snapshot!(ts::ty_decl(A::boxed()).unwrap().unwrap_or_default(), @r###"
+    export type A =
+      | string
+      | { thingy: string }
+      | null
+      | [number, number];
+    "###);
+    insta::assert_snapshot!(js::ty_decl(A::boxed()).unwrap().unwrap_or_default(), @"/** @typedef {string | { thingy: string } | null | [number, number]} tapi.tests.untagged_enum.A */");
+    insta::assert_snapshot!(fs::ty_decl(A::boxed()).unwrap().unwrap_or_default(), @r###"
+    [<JsonFSharpConverter(BaseUnionEncoding = JsonUnionEncoding.Untagged + JsonUnionEncoding.UnwrapSingleFieldCases)>]
+    type A =
+      | X of string
+      | Y of thingy: string
+      | Z
+      | W of int32 * int32
+    "###);
+}
+
+#[test]
+fn untagged_unit_enum() {
+    // let _ = color_eyre::install();
+    #[derive(Tapi, Serialize)]
+    #[tapi(krate = "crate")]
+    #[serde(untagged)]
+    enum A {
+        X,
+        Y,
+    }
+
+    insta::assert_snapshot!(serde_json::to_string(&A::X).unwrap(), @"null");
+    insta::assert_snapshot!(ts::ty_decl(A::boxed()).unwrap().unwrap_or_default(), @r###"
+    export type A =
+      | null;
+    "###);
+}
+
+#[test]
+fn flatten_struct() {
+    // let _ = color_eyre::install();
+    #[derive(Tapi, Serialize)]
+    #[tapi(krate = "crate")]
+    struct A {
+        a: i32,
+        #[serde(flatten)]
+        b: B,
+        #[serde(flatten)]
+        rest: std::collections::BTreeMap<String, String>,
+    }
+    #[derive(Tapi, Serialize)]
+    #[tapi(krate = "crate")]
+    struct B {
+        x: String,
+        y: bool,
+    }
+
+    let sample = A {
+        a: 1,
+        b: B {
+            x: "...".to_string(),
+            y: true,
+        },
+        rest: [("z".to_string(), "123".to_string())].into_iter().collect(),
+    };
+    insta::assert_snapshot!(serde_json::to_string_pretty(&sample).unwrap(), @r###"
+    {
+      "a": 1,
+      "x": "...",
+      "y": true,
+      "z": "123"
+    }
+    "###);
+
+    insta::assert_snapshot!(ts::ty_decl(A::boxed()).unwrap().unwrap_or_default(), @r###"
+    export type A = {
+      a: number
+    } & tapi.tests.flatten_struct.B & { [key: string]: string };
+    "###);
+    insta::assert_snapshot!(js::ty_decl(A::boxed()).unwrap().unwrap_or_default(), @r###"
+    /**
+     * @typedef {{ a: number } & tapi.tests.flatten_struct.B & { [key: string]: string }} tapi.tests.flatten_struct.A */
+    "###);
+    insta::assert_snapshot!(fs::ty_decl(A::boxed()).unwrap().unwrap_or_default(), @r###"
+    type A =
+      { a: int32
+        x: string
+        y: bool
+        [<JsonExtensionData>] rest: System.Collections.Generic.Dictionary<string, System.Text.Json.JsonElement> }
+    "###);
+    insta::assert_snapshot!(zod::ty_decl(A::boxed()).unwrap().unwrap_or_default(), @r###"
+    export const ASchema = z.object({
+      a: z.number(),
+    }).and(z.lazy(() => tapi.tests.flatten_struct.BSchema)).catchall(z.string());
+    export type A = z.infer<typeof ASchema>;
+    "###);
+    insta::assert_snapshot!(serde_json::to_string_pretty(&json_schema::ty_decl(A::boxed(), json_schema::REF_PREFIX).unwrap()).unwrap(), @r###"
+    {
+      "additionalProperties": {
+        "type": "string"
+      },
+      "allOf": [
+        {
+          "$ref": "#/$defs/tapi.tests.flatten_struct.B"
+        }
+      ],
+      "properties": {
+        "a": {
+          "format": "int32",
+          "type": "integer"
+        }
+      },
+      "required": [
+        "a"
+      ],
+      "type": "object"
+    }
+    "###);
+}
+
+#[test]
+fn flatten_enum_variant() {
+    // let _ = color_eyre::install();
+    #[derive(Tapi, Serialize)]
+    #[tapi(krate = "crate")]
+    #[serde(tag = "type")]
+    enum A {
+        X {
+            wow: String,
+            #[serde(flatten)]
+            b: B,
+        },
+        Z,
+    }
+    #[derive(Tapi, Serialize)]
+    #[tapi(krate = "crate")]
+    struct B {
+        x: String,
+    }
+
+    insta::assert_snapshot!(ts::ty_decl(A::boxed()).unwrap().unwrap_or_default(), @r###"
+    export type A =
+      | { "type": "X", wow: string } & tapi.tests.flatten_enum_variant.B
+      | { "type": "Z" };
+    "###);
+    insta::assert_snapshot!(fs::ty_decl(A::boxed()).unwrap().unwrap_or_default(), @r###"
+    [<JsonFSharpConverter(BaseUnionEncoding = JsonUnionEncoding.UnwrapSingleFieldCases, UnionTagName = "type")>]
+    type A =
+      | X of wow: string * x: string
+      | Z
+    "###);
+}
+
+#[test]
+fn optional_fields() {
+    #[derive(Tapi, Serialize)]
+    #[tapi(krate = "crate")]
+    struct A {
+        required: Option<i32>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        skipped: Option<i32>,
+        #[serde(default)]
+        defaulted: Option<i32>,
+        #[serde(default, skip_serializing_if = "std::option::Option::is_none")]
+        both: Option<String>,
+        #[serde(default)]
+        not_option: Vec<i32>,
+        #[serde(skip_serializing_if = "Vec::is_empty")]
+        tags: Vec<String>,
+    }
+
+    insta::assert_snapshot!(ts::ty_decl(A::boxed()).unwrap().unwrap_or_default(), @r###"
+    export type AInput = {
+      required?: (number | null),
+      skipped?: (number | null),
+      defaulted?: (number | null),
+      both?: (string | null),
+      not_option?: number[],
+      tags: string[]
+    };
+    export type AOutput = {
+      required: (number | null),
+      skipped?: number,
+      defaulted: (number | null),
+      both?: string,
+      not_option: number[],
+      tags?: string[]
+    };
+    export type A = AOutput;
+    "###);
+    insta::assert_snapshot!(js::ty_decl(A::boxed()).unwrap().unwrap_or_default(), @r###"
+    /**
+     * @typedef {{ required?: (number | null), skipped?: (number | null), defaulted?: (number | null), both?: (string | null), not_option?: number[], tags: string[] }} tapi.tests.optional_fields.AInput */
+    /**
+     * @typedef {{ required: (number | null), skipped?: number, defaulted: (number | null), both?: string, not_option: number[], tags?: string[] }} tapi.tests.optional_fields.AOutput */
+    /**
+     * @typedef {tapi.tests.optional_fields.AOutput} tapi.tests.optional_fields.A */
+    "###);
+    insta::assert_snapshot!(zod::ty_decl(A::boxed()).unwrap().unwrap_or_default(), @r###"
+    export const ASchema = z.object({
+      required: z.nullable(z.number()),
+      skipped: z.number().optional(),
+      defaulted: z.nullable(z.number()),
+      both: z.string().optional(),
+      not_option: z.array(z.number()),
+      tags: z.array(z.string()).optional(),
+    });
+    export type A = z.infer<typeof ASchema>;
+    "###);
+    insta::assert_snapshot!(serde_json::to_string_pretty(&json_schema::ty_decl(A::boxed(), json_schema::REF_PREFIX).unwrap()).unwrap(), @r###"
+    {
+      "properties": {
+        "both": {
+          "anyOf": [
+            {
+              "type": "string"
+            },
+            {
+              "type": "null"
+            }
+          ]
+        },
+        "defaulted": {
+          "anyOf": [
+            {
+              "format": "int32",
+              "type": "integer"
+            },
+            {
+              "type": "null"
+            }
+          ]
+        },
+        "not_option": {
+          "items": {
+            "format": "int32",
+            "type": "integer"
+          },
+          "type": "array"
+        },
+        "required": {
+          "anyOf": [
+            {
+              "format": "int32",
+              "type": "integer"
+            },
+            {
+              "type": "null"
+            }
+          ]
+        },
+        "skipped": {
+          "anyOf": [
+            {
+              "format": "int32",
+              "type": "integer"
+            },
+            {
+              "type": "null"
+            }
+          ]
+        },
+        "tags": {
+          "items": {
+            "type": "string"
+          },
+          "type": "array"
+        }
+      },
+      "required": [
+        "not_option"
+      ],
+      "type": "object"
+    }
+    "###);
+
+    // NOTE: `default` only applies when deserializing
+    #[derive(Tapi)]
+    #[tapi(krate = "crate")]
+    struct B {
+        #[serde(default)]
+        defaulted: Option<i32>,
+    }
+    assert!(ts::differs(B::boxed()));
+    insta::assert_snapshot!(ts::ty_decl(B::boxed()).unwrap().unwrap_or_default(), @r###"
+    export type BInput = {
+      defaulted?: (number | null)
+    };
+    export type BOutput = {
+      defaulted: (number | null)
+    };
+    export type B = BOutput;
+    "###);
+}
+
+#[test]
+fn input_output_views() {
+    #[derive(Tapi, Serialize)]
+    #[tapi(krate = "crate")]
+    #[serde(default)]
+    struct Settings {
+        #[serde(rename(serialize = "displayName", deserialize = "display_name"))]
+        name: String,
+        #[serde(alias = "colour")]
+        color: String,
+        #[serde(skip_deserializing)]
+        updated_at: u64,
+    }
+    #[derive(Tapi, Serialize)]
+    #[tapi(krate = "crate")]
+    struct User {
+        id: u64,
+        settings: Vec<Settings>,
+    }
+    #[derive(Tapi, Serialize)]
+    #[tapi(krate = "crate")]
+    struct Unchanged {
+        id: u64,
+    }
+
+    insta::assert_snapshot!(types(ts::builder(), User::all_dependencies()), @r###"
+    export namespace tapi {
+      export namespace tests {
+        export namespace input_output_views {
+          export type UserInput = {
+            id: number,
+            settings: tapi.tests.input_output_views.SettingsInput[]
+          };
+          export type UserOutput = {
+            id: number,
+            settings: tapi.tests.input_output_views.Settings[]
+          };
+          export type User = UserOutput;
+          export type SettingsInput = {
+            display_name?: string
+          } & ({ color?: string } | { colour?: string });
+          export type SettingsOutput = {
+            displayName: string,
+            color: string,
+            updated_at: number
+          };
+          export type Settings = SettingsOutput;
+        }
+      }
+    }
+    "###);
+    insta::assert_snapshot!(js::ty_decl(Settings::boxed()).unwrap().unwrap_or_default(), @r###"
+    /**
+     * @typedef {{ display_name?: string } & ({ color?: string } | { colour?: string })} tapi.tests.input_output_views.SettingsInput */
+    /**
+     * @typedef {{ displayName: string, color: string, updated_at: number }} tapi.tests.input_output_views.SettingsOutput */
+    /**
+     * @typedef {tapi.tests.input_output_views.SettingsOutput} tapi.tests.input_output_views.Settings */
+    "###);
+    insta::assert_snapshot!(ts::ty_decl(Unchanged::boxed()).unwrap().unwrap_or_default(), @r###"
+    export type Unchanged = {
+      id: number
+    };
+    "###);
+}
+
+#[test]
+fn doc_comments() {
+    /// A person.
+    ///
+    /// Has a name.
+    #[derive(Tapi, Serialize)]
+    #[tapi(krate = "crate")]
+    struct Person {
+        /// The full name.
+        name: String,
+        age: u8,
+    }
+    /// How a request went.
+    #[derive(Tapi, Serialize)]
+    #[tapi(krate = "crate")]
+    enum Status {
+        /// Everything is fine.
+        Ok,
+        Failed {
+            /// What went wrong.
+            reason: String,
+        },
+    }
+
+    insta::assert_snapshot!(ts::ty_decl(Person::boxed()).unwrap().unwrap_or_default(), @r###"
+    /**
+     * A person.
+     *
+     * Has a name.
+     */
+    export type Person = {
+      /** The full name. */
+      name: string,
+      age: number
+    };
+    "###);
+    insta::assert_snapshot!(ts::ty_decl(Status::boxed()).unwrap().unwrap_or_default(), @r###"
+    /** How a request went. */
+    export type Status =
+      /** Everything is fine. */
+      | "Ok"
+      | { "Failed": { /** What went wrong. */ reason: string } };
+    "###);
+    insta::assert_snapshot!(js::ty_decl(Person::boxed()).unwrap().unwrap_or_default(), @r###"
+    /**
+     * A person.
+     *
+     * Has a name.
+     * @typedef {object} tapi.tests.doc_comments.Person
+     * @property {string} name - The full name.
+     * @property {number} age
+     */
+    "###);
+    insta::assert_snapshot!(js::ty_decl(Status::boxed()).unwrap().unwrap_or_default(), @r###"
+    /**
+     * How a request went.
+     * @typedef {"Ok" | { "Failed": { reason: string } }} tapi.tests.doc_comments.Status */
+    "###);
+    insta::assert_snapshot!(fs::ty_decl(Person::boxed()).unwrap().unwrap_or_default(), @r###"
+    /// A person.
+    ///
+    /// Has a name.
+    type Person =
+      { /// The full name.
+        name: string
+        age: uint8 }
+    "###);
+    insta::assert_snapshot!(fs::ty_decl(Status::boxed()).unwrap().unwrap_or_default(), @r###"
+    /// How a request went.
+    [<JsonFSharpConverter(BaseUnionEncoding = JsonUnionEncoding.ExternalTag + JsonUnionEncoding.UnwrapFieldlessTags + JsonUnionEncoding.UnwrapSingleFieldCases)>]
+    type Status =
+      /// Everything is fine.
+      | Ok
+      | Failed of reason: string
+    "###);
+}
+
+/// The declarations `builder` emits for `tys`, without its prelude.
+fn types(builder: crate::builder::TypesBuilder, tys: Vec<DynTapi>) -> String {
+    crate::builder::TypesBuilder {
+        prelude: String::new(),
+        ..builder
+    }
+    .types(tys)
+    .unwrap()
+}
+
+#[test]
+fn generic_struct() {
+    #[derive(Tapi, Serialize)]
+    #[tapi(krate = "crate")]
+    struct Page<T> {
+        items: Vec<T>,
+        next: Option<String>,
+    }
+    #[derive(Tapi, Serialize)]
+    #[tapi(krate = "crate")]
+    struct User {
+        name: String,
+    }
+    #[derive(Tapi, Serialize)]
+    #[tapi(krate = "crate")]
+    struct Feed {
+        users: Page<User>,
+        ids: Page<u32>,
+    }
+
+    let tys = Feed::all_dependencies();
+    insta::assert_snapshot!(types(ts::builder(), tys.clone()), @r###"
+    export namespace tapi {
+      export namespace tests {
+        export namespace generic_struct {
+          export type PageInput<T> = {
+            items: T[],
+            next?: (string | null)
+          };
+          export type PageOutput<T> = {
+            items: T[],
+            next: (string | null)
+          };
+          export type Page<T> = PageOutput<T>;
+          export type FeedInput = {
+            users: tapi.tests.generic_struct.PageInput<tapi.tests.generic_struct.User>,
+            ids: tapi.tests.generic_struct.PageInput<number>
+          };
+          export type FeedOutput = {
+            users: tapi.tests.generic_struct.Page<tapi.tests.generic_struct.User>,
+            ids: tapi.tests.generic_struct.Page<number>
+          };
+          export type Feed = FeedOutput;
+          export type User = {
+            name: string
+          };
+        }
+      }
+    }
+    "###);
+    insta::assert_snapshot!(js::ty_decl(<Page<User>>::boxed()).unwrap().unwrap_or_default(), @r###"
+    /**
+     * @template T
+     * @typedef {{ items: T[], next?: (string | null) }} tapi.tests.generic_struct.PageInput */
+    /**
+     * @template T
+     * @typedef {{ items: T[], next: (string | null) }} tapi.tests.generic_struct.PageOutput */
+    /**
+     * @template T
+     * @typedef {tapi.tests.generic_struct.PageOutput<T>} tapi.tests.generic_struct.Page */
+    "###);
+    insta::assert_snapshot!(types(fs::builder(), tys.clone()), @r###"
+    module tapi =
+      module tests =
+        module generic_struct =
+          type Page<'T> =
+            { items: List<'T>
+              next: Option<string> }
+          type Feed =
+            { users: tapi.tests.generic_struct.Page<tapi.tests.generic_struct.User>
+              ids: tapi.tests.generic_struct.Page<uint32> }
+          type User =
+            { name: string }
+        
+      
+    "###);
+    insta::assert_snapshot!(types(zod::builder(), tys.clone()), @r###"
+    export namespace tapi {
+      export namespace tests {
+        export namespace generic_struct {
+          export const PageSchema = <T extends z.ZodTypeAny>(T: T) => z.object({
+            items: z.array(T),
+            next: z.nullable(z.string()),
+          });
+          export type Page<T> = z.infer<ReturnType<typeof PageSchema<z.ZodType<T>>>>;
+          export const FeedSchema = z.object({
+            users: z.lazy(() => tapi.tests.generic_struct.PageSchema(z.lazy(() => tapi.tests.generic_struct.UserSchema))),
+            ids: z.lazy(() => tapi.tests.generic_struct.PageSchema(z.number())),
+          });
+          export type Feed = z.infer<typeof FeedSchema>;
+          export const UserSchema = z.object({
+            name: z.string(),
+          });
+          export type User = z.infer<typeof UserSchema>;
+        }
+      }
+    }
+    "###);
+    insta::assert_snapshot!(serde_json::to_string_pretty(&json_schema::schema(Feed::boxed()).unwrap()).unwrap(), @r###"
+    {
+      "$defs": {
+        "tapi.tests.generic_struct.Feed": {
+          "properties": {
+            "ids": {
+              "$ref": "#/$defs/tapi.tests.generic_struct.Page_u32"
+            },
+            "users": {
+              "$ref": "#/$defs/tapi.tests.generic_struct.Page_tapi.tests.generic_struct.User"
+            }
+          },
+          "required": [
+            "users",
+            "ids"
+          ],
+          "type": "object"
+        },
+        "tapi.tests.generic_struct.Page_tapi.tests.generic_struct.User": {
+          "properties": {
+            "items": {
+              "items": {
+                "$ref": "#/$defs/tapi.tests.generic_struct.User"
+              },
+              "type": "array"
+            },
+            "next": {
+              "anyOf": [
+                {
+                  "type": "string"
+                },
+                {
+                  "type": "null"
+                }
+              ]
+            }
+          },
+          "required": [
+            "items"
+          ],
+          "type": "object"
+        },
+        "tapi.tests.generic_struct.Page_u32": {
+          "properties": {
+            "items": {
+              "items": {
+                "format": "uint32",
+                "minimum": 0,
+                "type": "integer"
+              },
+              "type": "array"
+            },
+            "next": {
+              "anyOf": [
+                {
+                  "type": "string"
+                },
+                {
+                  "type": "null"
+                }
+              ]
+            }
+          },
+          "required": [
+            "items"
+          ],
+          "type": "object"
+        },
+        "tapi.tests.generic_struct.User": {
+          "properties": {
+            "name": {
+              "type": "string"
+            }
+          },
+          "required": [
+            "name"
+          ],
+          "type": "object"
+        }
+      },
+      "$ref": "#/$defs/tapi.tests.generic_struct.Feed",
+      "$schema": "https://json-schema.org/draft/2020-12/schema"
+    }
+    "###);
+}
+
+#[test]
+fn generic_enum() {
+    #[derive(Tapi, Serialize)]
+    #[tapi(krate = "crate")]
+    #[serde(tag = "type")]
+    enum Outcome<T, E> {
+        Done { value: T },
+        Failed { error: E },
+    }
+
+    assert_ne!(Outcome::<String, u32>::id(), Outcome::<u32, u32>::id());
+
+    let tys = <Vec<(Outcome<String, u32>, Outcome<bool, u32>)>>::all_dependencies();
+    insta::assert_snapshot!(types(ts::builder(), tys.clone()), @r###"
+    export namespace tapi {
+      export namespace tests {
+        export namespace generic_enum {
+          export type Outcome<T, E> =
+            | { "type": "Done", value: T }
+            | { "type": "Failed", error: E };
+        }
+      }
+    }
+    "###);
+    insta::assert_snapshot!(types(zod::builder(), tys.clone()), @r###"
+    export namespace tapi {
+      export namespace tests {
+        export namespace generic_enum {
+          export const OutcomeSchema = <T extends z.ZodTypeAny, E extends z.ZodTypeAny>(T: T, E: E) => z.discriminatedUnion("type", [
+            z.object({ "type": z.literal("Done"), value: T }),
+            z.object({ "type": z.literal("Failed"), error: E }),
+          ]);
+          export type Outcome<T, E> = z.infer<ReturnType<typeof OutcomeSchema<z.ZodType<T>, z.ZodType<E>>>>;
+        }
+      }
+    }
+    "###);
+}
+
+#[test]
+fn shared_subtypes() {
+    // NOTE: each level refers to the next twice, so walking every path
+    // rather than every type takes exponential time
+    macro_rules! levels {
+        ($last:ident) => {
+            #[derive(Tapi)]
+            #[tapi(krate = "crate", path = "levels")]
+            struct $last {
+                #[serde(skip_deserializing)]
+                id: u32,
+            }
+        };
+        ($level:ident $next:ident $($rest:ident)*) => {
+            #[derive(Tapi)]
+            #[tapi(krate = "crate", path = "levels")]
+            struct $level {
+                l: $next,
+                r: $next,
+            }
+            levels!($next $($rest)*);
+        };
+    }
+    levels!(L0 L1 L2 L3 L4 L5 L6 L7 L8 L9 L10 L11 L12 L13 L14 L15 L16 L17 L18 L19 L20 L21 L22 L23 L24 L25 L26 L27 L28 L29);
+
+    let ts = types(ts::builder(), L0::all_dependencies());
+    // NOTE: the leaf differs, and so does every level referring to it
+    assert!(ts.contains("export type L0Input = {\n    l: levels.L1Input,"));
+    assert_eq!(ts.matches("Input = {").count(), 30);
+}
+
+#[test]
+fn recursive_types() {
+    #[derive(Tapi)]
+    #[tapi(krate = "crate")]
+    enum Expr {
+        Lit(i64),
+        Add(Box<Expr>, Box<Expr>),
+        Let {
+            name: String,
+            value: std::rc::Rc<Expr>,
+            body: Box<Expr>,
+        },
+    }
+    #[derive(Tapi)]
+    #[tapi(krate = "crate")]
+    struct Tree {
+        root: Node,
+    }
+    #[derive(Tapi)]
+    #[tapi(krate = "crate")]
+    struct Node {
+        children: Vec<Node>,
+        subtree: Option<std::sync::Arc<Tree>>,
+        expr: Option<Box<Expr>>,
+    }
+    #[derive(Tapi)]
+    #[tapi(krate = "crate")]
+    struct List<T> {
+        head: T,
+        tail: Option<Box<List<T>>>,
+    }
+
+    assert_eq!(<Box<Expr>>::id(), Expr::id());
+
+    let tys = Tree::all_dependencies();
+    let ts = types(ts::builder(), tys.clone());
+    for decl in [
+        "export type Expr =",
+        "export type Tree =",
+        "export type Node =",
+    ] {
+        assert_eq!(ts.matches(decl).count(), 1, "{decl} is declared once");
+    }
+    insta::assert_snapshot!(ts, @r###"
+    export namespace tapi {
+      export namespace tests {
+        export namespace recursive_types {
+          export type NodeInput = {
+            children: tapi.tests.recursive_types.NodeInput[],
+            subtree?: (tapi.tests.recursive_types.TreeInput | null),
+            expr?: (tapi.tests.recursive_types.Expr | null)
+          };
+          export type NodeOutput = {
+            children: tapi.tests.recursive_types.Node[],
+            subtree: (tapi.tests.recursive_types.Tree | null),
+            expr: (tapi.tests.recursive_types.Expr | null)
+          };
+          export type Node = NodeOutput;
+          export type TreeInput = {
+            root: tapi.tests.recursive_types.NodeInput
+          };
+          export type TreeOutput = {
+            root: tapi.tests.recursive_types.Node
+          };
+          export type Tree = TreeOutput;
+          export type Expr =
+            | { "Lit": number }
+            | { "Add": [tapi.tests.recursive_types.Expr, tapi.tests.recursive_types.Expr] }
+            | { "Let": { name: string, value: tapi.tests.recursive_types.Expr, body: tapi.tests.recursive_types.Expr } };
+        }
+      }
+    }
+    "###);
+    insta::assert_snapshot!(types(zod::builder(), tys.clone()), @r###"
+    export namespace tapi {
+      export namespace tests {
+        export namespace recursive_types {
+          export type Node = {
+            children: tapi.tests.recursive_types.Node[],
+            subtree: (tapi.tests.recursive_types.Tree | null),
+            expr: (tapi.tests.recursive_types.Expr | null)
+          };
+          export const NodeSchema: z.ZodType<Node> = z.object({
+            children: z.array(z.lazy(() => tapi.tests.recursive_types.NodeSchema)),
+            subtree: z.nullable(z.lazy(() => tapi.tests.recursive_types.TreeSchema)),
+            expr: z.nullable(z.lazy(() => tapi.tests.recursive_types.ExprSchema)),
+          });
+          export type Tree = {
+            root: tapi.tests.recursive_types.Node
+          };
+          export const TreeSchema: z.ZodType<Tree> = z.object({
+            root: z.lazy(() => tapi.tests.recursive_types.NodeSchema),
+          });
+          export type Expr =
+            | { "Lit": number }
+            | { "Add": [tapi.tests.recursive_types.Expr, tapi.tests.recursive_types.Expr] }
+            | { "Let": { name: string, value: tapi.tests.recursive_types.Expr, body: tapi.tests.recursive_types.Expr } };
+          export const ExprSchema: z.ZodType<Expr> = z.union([
+            z.object({ "Lit": z.number() }),
+            z.object({ "Add": z.tuple([z.lazy(() => tapi.tests.recursive_types.ExprSchema), z.lazy(() => tapi.tests.recursive_types.ExprSchema)]) }),
+            z.object({ "Let": z.object({ name: z.string(), value: z.lazy(() => tapi.tests.recursive_types.ExprSchema), body: z.lazy(() => tapi.tests.recursive_types.ExprSchema) }) }),
+          ]);
+        }
+      }
+    }
+    "###);
+    insta::assert_snapshot!(types(zod::builder(), <List<Expr>>::all_dependencies()), @r###"
+    export namespace tapi {
+      export namespace tests {
+        export namespace recursive_types {
+          export type Expr =
+            | { "Lit": number }
+            | { "Add": [tapi.tests.recursive_types.Expr, tapi.tests.recursive_types.Expr] }
+            | { "Let": { name: string, value: tapi.tests.recursive_types.Expr, body: tapi.tests.recursive_types.Expr } };
+          export const ExprSchema: z.ZodType<Expr> = z.union([
+            z.object({ "Lit": z.number() }),
+            z.object({ "Add": z.tuple([z.lazy(() => tapi.tests.recursive_types.ExprSchema), z.lazy(() => tapi.tests.recursive_types.ExprSchema)]) }),
+            z.object({ "Let": z.object({ name: z.string(), value: z.lazy(() => tapi.tests.recursive_types.ExprSchema), body: z.lazy(() => tapi.tests.recursive_types.ExprSchema) }) }),
+          ]);
+          export type List<T> = {
+            head: T,
+            tail: (tapi.tests.recursive_types.List<T> | null)
+          };
+          export const ListSchema = <T extends z.ZodTypeAny>(T: T): z.ZodType<List<z.infer<T>>> => z.object({
+            head: T,
+            tail: z.nullable(z.lazy(() => tapi.tests.recursive_types.ListSchema(T))),
+          });
+        }
+      }
+    }
+    "###);
+    insta::assert_snapshot!(serde_json::to_string_pretty(&json_schema::schema(Expr::boxed()).unwrap()).unwrap(), @r###"
+    {
+      "$defs": {
+        "tapi.tests.recursive_types.Expr": {
+          "oneOf": [
+            {
+              "additionalProperties": false,
+              "properties": {
+                "Lit": {
+                  "format": "int64",
+                  "type": "integer"
+                }
+              },
+              "required": [
+                "Lit"
+              ],
+              "type": "object"
+            },
+            {
+              "additionalProperties": false,
+              "properties": {
+                "Add": {
+                  "maxItems": 2,
+                  "minItems": 2,
+                  "prefixItems": [
+                    {
+                      "$ref": "#/$defs/tapi.tests.recursive_types.Expr"
+                    },
+                    {
+                      "$ref": "#/$defs/tapi.tests.recursive_types.Expr"
+                    }
+                  ],
+                  "type": "array"
+                }
+              },
+              "required": [
+                "Add"
+              ],
+              "type": "object"
+            },
+            {
+              "additionalProperties": false,
+              "properties": {
+                "Let": {
+                  "properties": {
+                    "body": {
+                      "$ref": "#/$defs/tapi.tests.recursive_types.Expr"
+                    },
+                    "name": {
+                      "type": "string"
+                    },
+                    "value": {
+                      "$ref": "#/$defs/tapi.tests.recursive_types.Expr"
+                    }
+                  },
+                  "required": [
+                    "name",
+                    "value",
+                    "body"
+                  ],
+                  "type": "object"
+                }
+              },
+              "required": [
+                "Let"
+              ],
+              "type": "object"
+            }
+          ]
+        }
+      },
+      "$ref": "#/$defs/tapi.tests.recursive_types.Expr",
+      "$schema": "https://json-schema.org/draft/2020-12/schema"
+    }
+    "###);
+}
+
+#[test]
+fn name_collisions() {
+    mod users {
+        #[derive(crate::Tapi)]
+        #[tapi(krate = "crate", path = "api")]
+        pub struct Error {
+            pub user: String,
+        }
+    }
+    mod posts {
+        #[derive(crate::Tapi)]
+        #[tapi(krate = "crate", path = "api")]
+        pub struct Error {
+            pub post: u64,
+        }
+        #[derive(crate::Tapi)]
+        #[tapi(krate = "crate", path = "api", name = "PostError")]
+        #[serde(rename = "Error")]
+        pub struct Renamed {
+            pub post: u64,
+        }
+    }
+
+    let err = ts::builder()
+        .types([users::Error::boxed(), posts::Error::boxed()])
+        .unwrap_err();
+    assert_eq!(
+        err,
+        crate::Error::NameCollision(crate::builder::NameCollision {
+            name: "api.Error".to_string(),
+            tys: [
+                "tapi::tests::name_collisions::users::Error",
+                "tapi::tests::name_collisions::posts::Error"
+            ],
+        })
+    );
+    insta::assert_snapshot!(err, @r###"`tapi::tests::name_collisions::users::Error` and `tapi::tests::name_collisions::posts::Error` are both declared as `api.Error`, use `#[tapi(name = "...")]` to rename one of them"###);
+    assert_eq!(
+        json_schema::defs(
+            [users::Error::boxed(), posts::Error::boxed()],
+            json_schema::REF_PREFIX
+        )
+        .unwrap_err(),
+        err
+    );
+    assert_eq!(
+        openapi::schemas([users::Error::boxed(), posts::Error::boxed()]).unwrap_err(),
+        err
+    );
+    assert_eq!(
+        openapi::schemas([users::Error::boxed(), posts::Renamed::boxed()])
+            .unwrap()
+            .keys()
+            .collect::<Vec<_>>(),
+        ["api.Error", "api.PostError"]
+    );
+
+    let ts = types(
+        ts::builder(),
+        vec![users::Error::boxed(), posts::Renamed::boxed()],
+    );
+    insta::assert_snapshot!(ts, @r###"
+    export namespace api {
+      export type Error = {
+        user: string
+      };
+      export type PostError = {
+        post: number
+      };
+    }
+    "###);
+}
+
+#[test]
+fn unsupported_shapes() {
+    use crate::kind::{FieldName, TypeKind};
+
+    #[derive(Tapi)]
+    #[tapi(krate = "crate")]
+    struct Point {
+        x: f64,
+    }
+    /// A manual implementation with a field no derived one would have.
+    struct Indexed;
+    impl Tapi for Indexed {
+        fn name() -> &'static str {
+            "Indexed"
+        }
+        fn kind() -> TypeKind {
+            let TypeKind::Struct(mut s) = Point::kind() else {
+                unreachable!()
+            };
+            s.fields[0].name = FieldName::Index(0);
+            TypeKind::Struct(s)
+        }
+    }
+    struct Hidden;
+    impl Tapi for Hidden {
+        fn name() -> &'static str {
+            "Hidden"
+        }
+        fn kind() -> TypeKind {
+            let TypeKind::Struct(mut s) = Point::kind() else {
+                unreachable!()
+            };
+            s.attr.transparent = true;
+            s.fields[0].attr.skip_serializing = true;
+            TypeKind::Struct(s)
+        }
+    }
+
+    let err = ts::ty_decl(Indexed::boxed()).unwrap_err();
+    insta::assert_snapshot!(err, @"field `0` of `tapi::tests::unsupported_shapes::Indexed` has no name, but belongs to a struct with named fields");
+    assert_eq!(err, zod::ty_decl(Indexed::boxed()).unwrap_err());
+    assert_eq!(err, fs::ty_decl(Indexed::boxed()).unwrap_err());
+    assert_eq!(err, json_schema::schema(Indexed::boxed()).unwrap_err());
+
+    let err = ts::builder().types([Hidden::boxed()]).unwrap_err();
+    insta::assert_snapshot!(err, @"`tapi::tests::unsupported_shapes::Hidden` is `#[serde(transparent)]`, but every field is skipped");
+    assert_eq!(err, js::ty_decl(Hidden::boxed()).unwrap_err());
+    // NOTE: only skipped when serializing, so it can still be declared as
+    // input
+    assert!(ts::view_decl(Hidden::boxed(), crate::kind::View::Input, "Input").is_ok());
+}
+
+#[test]
+fn internally_tagged_newtype() {
+    #[derive(Tapi)]
+    #[tapi(krate = "crate")]
+    struct Circle {
+        radius: f64,
+    }
+    #[derive(Tapi)]
+    #[tapi(krate = "crate")]
+    #[serde(tag = "type")]
+    enum Shape {
+        Circle(Circle),
+        Square { side: f64 },
+    }
+
+    insta::assert_snapshot!(ts::ty_decl(Shape::boxed()).unwrap().unwrap_or_default(), @r###"
+    export type Shape =
+      | { "type": "Circle" } & tapi.tests.internally_tagged_newtype.Circle
+      | { "type": "Square", side: number };
+    "###);
+    insta::assert_snapshot!(js::ty_decl(Shape::boxed()).unwrap().unwrap_or_default(), @r###"/** @typedef {{ "type": "Circle" } & tapi.tests.internally_tagged_newtype.Circle | { "type": "Square", side: number }} tapi.tests.internally_tagged_newtype.Shape */"###);
+}
+
+#[test]
+fn generic_bounds() {
+    #[derive(Tapi)]
+    #[tapi(krate = "crate")]
+    struct Matrix<const N: usize> {
+        rows: [[f64; N]; N],
+    }
+    #[derive(Tapi, Serialize)]
+    #[tapi(krate = "crate")]
+    struct Wrapper<'a, T: Clone + Serialize + 'a>
+    where
+        T: Default,
+    {
+        value: T,
+        #[serde(skip)]
+        _borrowed: std::marker::PhantomData<&'a ()>,
+    }
+    #[derive(Tapi)]
+    #[tapi(krate = "crate", bound = "T: Tapi + Clone")]
+    struct Cached<T> {
+        value: T,
+    }
+    // NOTE: `Cached<T>` is only `Tapi` if `T: Clone`
+    #[derive(Tapi)]
+    #[tapi(krate = "crate", bound = "T: Tapi + Clone")]
+    struct Pair<T> {
+        left: Cached<T>,
+        right: Cached<T>,
+    }
+    trait Unit {}
+    #[derive(Tapi)]
+    #[tapi(krate = "crate")]
+    struct Meters;
+    impl Unit for Meters {}
+    #[derive(Tapi)]
+    #[tapi(krate = "crate")]
+    struct Measure<U: Unit> {
+        value: f64,
+        unit: U,
+        history: Vec<f64>,
+    }
+    // NOTE: `Measure<U>` is only `Tapi` if `U: Unit`, also when nested
+    #[derive(Tapi)]
+    #[tapi(krate = "crate")]
+    struct Log<U: Unit> {
+        latest: Measure<U>,
+        readings: Vec<Option<Box<Measure<U>>>>,
+        by_day: std::collections::BTreeMap<String, (Measure<U>, u32)>,
+    }
+    #[derive(Tapi)]
+    #[tapi(krate = "crate")]
+    struct Labeled<T>
+    where
+        T: std::fmt::Display,
+    {
+        label: T,
+        labels: Vec<T>,
+    }
+    type Size = usize;
+    #[derive(Tapi)]
+    #[tapi(krate = "crate")]
+    struct Buffer<const N: Size> {
+        bytes: [u8; N],
+    }
+    #[derive(Tapi)]
+    #[tapi(krate = "crate")]
+    struct Shapes {
+        square: Matrix<2>,
+        cube: Matrix<3>,
+        wrapped: Wrapper<'static, u32>,
+        pair: Pair<bool>,
+        length: Measure<Meters>,
+        log: Log<Meters>,
+        labeled: Labeled<String>,
+        buffer: Buffer<16>,
+    }
+
+    let tys = Shapes::all_dependencies();
+    insta::assert_snapshot!(types(ts::builder(), tys), @r###"
+    export namespace tapi {
+      export namespace tests {
+        export namespace generic_bounds {
+          export type Matrix = {
+            rows: number[][]
+          };
+          export type Wrapper<T> = {
+            value: T
+          };
+          export type Pair<T> = {
+            left: tapi.tests.generic_bounds.Cached<T>,
+            right: tapi.tests.generic_bounds.Cached<T>
+          };
+          export type Measure<U> = {
+            value: number,
+            unit: U,
+            history: number[]
+          };
+          export type Log<U> = {
+            latest: tapi.tests.generic_bounds.Measure<U>,
+            readings: (tapi.tests.generic_bounds.Measure<U> | null)[],
+            by_day: Record<string, [tapi.tests.generic_bounds.Measure<U>, number]>
+          };
+          export type Labeled<T> = {
+            label: T,
+            labels: T[]
+          };
+          export type Buffer = {
+            bytes: number[]
+          };
+          export type Shapes = {
+            square: tapi.tests.generic_bounds.Matrix,
+            cube: tapi.tests.generic_bounds.Matrix,
+            wrapped: tapi.tests.generic_bounds.Wrapper<number>,
+            pair: tapi.tests.generic_bounds.Pair<boolean>,
+            length: tapi.tests.generic_bounds.Measure<tapi.tests.generic_bounds.Meters>,
+            log: tapi.tests.generic_bounds.Log<tapi.tests.generic_bounds.Meters>,
+            labeled: tapi.tests.generic_bounds.Labeled<string>,
+            buffer: tapi.tests.generic_bounds.Buffer
+          };
+          export type Cached<T> = {
+            value: T
+          };
+          export type Meters = {
+          
+          };
+        }
+      }
+    }
+    "###);
+}
+
 #[test]
 fn new_kind_struct() {
     // let _ = color_eyre::install();
@@ -354,7 +1628,7 @@ fn new_kind_struct() {
         x: String,
         y: (Box<A>, i32),
     }
-    println!("{}", ts::ty_decl(A::boxed()).unwrap_or_default());
+    println!("{}", ts::ty_decl(A::boxed()).unwrap().unwrap_or_default());
 }
 
 #[test]
@@ -372,7 +1646,7 @@ fn new_kind_enum() {
         "{}",
         serde_json::to_string(&A::X("...".to_string())).unwrap()
     );
-    println!("{}", ts::ty_decl(A::boxed()).unwrap_or_default());
+    println!("{}", ts::ty_decl(A::boxed()).unwrap().unwrap_or_default());
 }
 
 #[test]
@@ -386,7 +1660,7 @@ fn new_kind_enum_tagged() {
         Y { thingy: String, other: Vec<A> },
         Z,
     }
-    println!("{}", ts::ty_decl(A::boxed()).unwrap_or_default());
+    println!("{}", ts::ty_decl(A::boxed()).unwrap().unwrap_or_default());
 }
 
 #[test]
@@ -401,5 +1675,408 @@ fn new_kind_enum_tagged_and_content() {
         Z,
         W(i32, i32),
     }
-    println!("{}", ts::ty_decl(A::boxed()).unwrap_or_default());
+    println!("{}", ts::ty_decl(A::boxed()).unwrap().unwrap_or_default());
+}
+
+#[test]
+fn openapi_schemas() {
+    #[derive(Tapi)]
+    #[tapi(krate = "crate")]
+    struct A {
+        x: Option<u32>,
+        y: Vec<B>,
+    }
+    #[derive(Tapi)]
+    #[tapi(krate = "crate")]
+    #[serde(tag = "type", content = "data")]
+    enum B {
+        X(String),
+        Y { thingy: (i32, bool) },
+        Z,
+    }
+
+    insta::assert_snapshot!(serde_json::to_string_pretty(&openapi::schemas(A::all_dependencies()).unwrap()).unwrap(), @r###"
+    {
+      "tapi.tests.openapi_schemas.A": {
+        "properties": {
+          "x": {
+            "anyOf": [
+              {
+                "format": "uint32",
+                "minimum": 0,
+                "type": "integer"
+              },
+              {
+                "type": "null"
+              }
+            ]
+          },
+          "y": {
+            "items": {
+              "$ref": "#/components/schemas/tapi.tests.openapi_schemas.B"
+            },
+            "type": "array"
+          }
+        },
+        "required": [
+          "y"
+        ],
+        "type": "object"
+      },
+      "tapi.tests.openapi_schemas.B": {
+        "oneOf": [
+          {
+            "properties": {
+              "data": {
+                "type": "string"
+              },
+              "type": {
+                "const": "X"
+              }
+            },
+            "required": [
+              "type",
+              "data"
+            ],
+            "type": "object"
+          },
+          {
+            "properties": {
+              "data": {
+                "properties": {
+                  "thingy": {
+                    "maxItems": 2,
+                    "minItems": 2,
+                    "prefixItems": [
+                      {
+                        "format": "int32",
+                        "type": "integer"
+                      },
+                      {
+                        "type": "boolean"
+                      }
+                    ],
+                    "type": "array"
+                  }
+                },
+                "required": [
+                  "thingy"
+                ],
+                "type": "object"
+              },
+              "type": {
+                "const": "Y"
+              }
+            },
+            "required": [
+              "type",
+              "data"
+            ],
+            "type": "object"
+          },
+          {
+            "properties": {
+              "type": {
+                "const": "Z"
+              }
+            },
+            "required": [
+              "type"
+            ],
+            "type": "object"
+          }
+        ]
+      }
+    }
+    "###);
+}
+
+#[test]
+fn json_schema_document() {
+    #[derive(Tapi)]
+    #[tapi(krate = "crate")]
+    struct Config {
+        name: Name,
+        #[serde(skip_serializing)]
+        secret: String,
+        limits: std::collections::HashMap<Kind, u32>,
+        value: Value,
+        event: Option<Event>,
+    }
+    #[derive(Tapi)]
+    #[tapi(krate = "crate")]
+    #[serde(transparent)]
+    struct Name {
+        inner: String,
+    }
+    #[derive(Tapi)]
+    #[tapi(krate = "crate")]
+    enum Kind {
+        Read,
+        Write,
+    }
+    #[derive(Tapi)]
+    #[tapi(krate = "crate")]
+    #[serde(untagged)]
+    enum Value {
+        Text(String),
+        Pair(i32, i32),
+        Nothing,
+    }
+    #[derive(Tapi)]
+    #[tapi(krate = "crate")]
+    #[serde(tag = "type")]
+    enum Event {
+        Created { id: u64 },
+        Renamed(Rename),
+        Deleted,
+    }
+    #[derive(Tapi)]
+    #[tapi(krate = "crate")]
+    struct Rename {
+        to: Name,
+    }
+
+    insta::assert_snapshot!(serde_json::to_string_pretty(&json_schema::schema(Config::boxed()).unwrap()).unwrap(), @r###"
+    {
+      "$defs": {
+        "tapi.tests.json_schema_document.Config": {
+          "properties": {
+            "event": {
+              "anyOf": [
+                {
+                  "$ref": "#/$defs/tapi.tests.json_schema_document.Event"
+                },
+                {
+                  "type": "null"
+                }
+              ]
+            },
+            "limits": {
+              "additionalProperties": {
+                "format": "uint32",
+                "minimum": 0,
+                "type": "integer"
+              },
+              "propertyNames": {
+                "$ref": "#/$defs/tapi.tests.json_schema_document.Kind"
+              },
+              "type": "object"
+            },
+            "name": {
+              "$ref": "#/$defs/tapi.tests.json_schema_document.Name"
+            },
+            "value": {
+              "$ref": "#/$defs/tapi.tests.json_schema_document.Value"
+            }
+          },
+          "required": [
+            "name",
+            "limits",
+            "value"
+          ],
+          "type": "object"
+        },
+        "tapi.tests.json_schema_document.Event": {
+          "oneOf": [
+            {
+              "properties": {
+                "id": {
+                  "format": "uint64",
+                  "minimum": 0,
+                  "type": "integer"
+                },
+                "type": {
+                  "const": "Created"
+                }
+              },
+              "required": [
+                "type",
+                "id"
+              ],
+              "type": "object"
+            },
+            {
+              "allOf": [
+                {
+                  "properties": {
+                    "type": {
+                      "const": "Renamed"
+                    }
+                  },
+                  "required": [
+                    "type"
+                  ],
+                  "type": "object"
+                },
+                {
+                  "$ref": "#/$defs/tapi.tests.json_schema_document.Rename"
+                }
+              ]
+            },
+            {
+              "properties": {
+                "type": {
+                  "const": "Deleted"
+                }
+              },
+              "required": [
+                "type"
+              ],
+              "type": "object"
+            }
+          ]
+        },
+        "tapi.tests.json_schema_document.Kind": {
+          "enum": [
+            "Read",
+            "Write"
+          ],
+          "type": "string"
+        },
+        "tapi.tests.json_schema_document.Name": {
+          "type": "string"
+        },
+        "tapi.tests.json_schema_document.Rename": {
+          "properties": {
+            "to": {
+              "$ref": "#/$defs/tapi.tests.json_schema_document.Name"
+            }
+          },
+          "required": [
+            "to"
+          ],
+          "type": "object"
+        },
+        "tapi.tests.json_schema_document.Value": {
+          "anyOf": [
+            {
+              "type": "string"
+            },
+            {
+              "maxItems": 2,
+              "minItems": 2,
+              "prefixItems": [
+                {
+                  "format": "int32",
+                  "type": "integer"
+                },
+                {
+                  "format": "int32",
+                  "type": "integer"
+                }
+              ],
+              "type": "array"
+            },
+            {
+              "type": "null"
+            }
+          ]
+        }
+      },
+      "$ref": "#/$defs/tapi.tests.json_schema_document.Config",
+      "$schema": "https://json-schema.org/draft/2020-12/schema"
+    }
+    "###);
+}
+
+#[test]
+fn zod_struct() {
+    #[derive(Tapi)]
+    #[tapi(krate = "crate")]
+    struct Person {
+        name: String,
+        age: Option<u8>,
+        friends: Vec<Person>,
+    }
+
+    insta::assert_snapshot!(zod::ty_decl(Person::boxed()).unwrap().unwrap_or_default(), @r###"
+    export type Person = {
+      name: string,
+      age: (number | null),
+      friends: tapi.tests.zod_struct.Person[]
+    };
+    export const PersonSchema: z.ZodType<Person> = z.object({
+      name: z.string(),
+      age: z.nullable(z.number()),
+      friends: z.array(z.lazy(() => tapi.tests.zod_struct.PersonSchema)),
+    });
+    "###);
+}
+
+#[test]
+fn zod_enums() {
+    #[derive(Tapi)]
+    #[tapi(krate = "crate")]
+    enum Color {
+        Red,
+        Green,
+    }
+    #[derive(Tapi)]
+    #[tapi(krate = "crate")]
+    #[serde(tag = "type")]
+    enum Internal {
+        X { wow: String },
+        Z,
+    }
+    #[derive(Tapi)]
+    #[tapi(krate = "crate")]
+    #[serde(tag = "type", content = "data")]
+    enum Adjacent {
+        X(String),
+        Y { thingy: String },
+        W(i32, i32),
+    }
+    #[derive(Tapi)]
+    #[tapi(krate = "crate")]
+    enum External {
+        X(String),
+        Z,
+    }
+
+    insta::assert_snapshot!(zod::ty_decl(Color::boxed()).unwrap().unwrap_or_default(), @r###"
+    export const ColorSchema = z.enum(["Red", "Green"]);
+    export type Color = z.infer<typeof ColorSchema>;
+    "###);
+    insta::assert_snapshot!(zod::ty_decl(Internal::boxed()).unwrap().unwrap_or_default(), @r###"
+    export const InternalSchema = z.discriminatedUnion("type", [
+      z.object({ "type": z.literal("X"), wow: z.string() }),
+      z.object({ "type": z.literal("Z") }),
+    ]);
+    export type Internal = z.infer<typeof InternalSchema>;
+    "###);
+    insta::assert_snapshot!(zod::ty_decl(Adjacent::boxed()).unwrap().unwrap_or_default(), @r###"
+    export const AdjacentSchema = z.discriminatedUnion("type", [
+      z.object({ "type": z.literal("X"), "data": z.string() }),
+      z.object({ "type": z.literal("Y"), "data": z.object({ thingy: z.string() }) }),
+      z.object({ "type": z.literal("W"), "data": z.tuple([z.number(), z.number()]) }),
+    ]);
+    export type Adjacent = z.infer<typeof AdjacentSchema>;
+    "###);
+    insta::assert_snapshot!(zod::ty_decl(External::boxed()).unwrap().unwrap_or_default(), @r###"
+    export const ExternalSchema = z.union([
+      z.object({ "X": z.string() }),
+      z.literal("Z"),
+    ]);
+    export type External = z.infer<typeof ExternalSchema>;
+    "###);
+}
+
+#[cfg(feature = "endpoints")]
+#[test]
+fn methods() {
+    use crate::endpoints::Method;
+
+    for method in [
+        Method::Get,
+        Method::Post,
+        Method::Put,
+        Method::Delete,
+        Method::Patch,
+        Method::Head,
+        Method::Options,
+        Method::Trace,
+    ] {
+        assert_eq!(Method::from_str(method.as_str()), Some(method));
+    }
 }
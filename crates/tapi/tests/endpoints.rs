@@ -0,0 +1,1244 @@
+#![cfg(feature = "endpoints")]
+
+mod typed_errors {
+    use axum::{
+        extract::Path,
+        http::StatusCode,
+        response::{IntoResponse, Response},
+        Json,
+    };
+    use tapi::{endpoints::Endpoint, Tapi};
+
+    #[derive(Tapi, serde::Serialize)]
+    pub struct ApiError {
+        message: String,
+    }
+    impl IntoResponse for ApiError {
+        fn into_response(self) -> Response {
+            (StatusCode::BAD_REQUEST, Json(self)).into_response()
+        }
+    }
+    /// An error that is not described, which is left out of the clients.
+    pub struct AppError;
+    impl IntoResponse for AppError {
+        fn into_response(self) -> Response {
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+
+    #[tapi::tapi(path = "/users/:id", method = Get)]
+    pub async fn get_user(Path(id): Path<u64>) -> Result<Json<u64>, ApiError> {
+        Ok(Json(id))
+    }
+    #[tapi::tapi(path = "/login", method = Post)]
+    pub async fn login() -> Result<(), (StatusCode, String)> {
+        Ok(())
+    }
+    #[tapi::tapi(path = "/status", method = Get)]
+    pub async fn status() -> Result<String, StatusCode> {
+        Ok(String::new())
+    }
+    #[tapi::tapi(path = "/config", method = Get)]
+    pub async fn config() -> Result<Json<String>, AppError> {
+        Ok(Json(String::new()))
+    }
+    #[tapi::tapi(path = "/health", method = Get)]
+    pub async fn health() -> Result<String, (StatusCode, &'static str)> {
+        Ok(String::new())
+    }
+
+    #[test]
+    fn typed_errors() {
+        let get_user: &dyn Endpoint<()> = &get_user::endpoint;
+        let login: &dyn Endpoint<()> = &login::endpoint;
+        for untyped in [
+            &status::endpoint as &dyn Endpoint<()>,
+            &config::endpoint,
+            &health::endpoint,
+        ] {
+            assert!(untyped.error().is_none(), "{}", untyped.path());
+        }
+
+        insta::assert_snapshot!(get_user.ts_client(), @r###"request<Record<string, never>, number, { id: number }, endpoints.typed_errors.ApiError>("none", "GET", "/users/:id", "json", { typedErrors: true })"###);
+        insta::assert_snapshot!(login.ts_client(), @r###"request<Record<string, never>, void, never, string>("none", "POST", "/login", "none", { typedErrors: true })"###);
+        insta::assert_snapshot!(get_user.js_client(), @r###"
+        /** @type {ReturnType<typeof request<Record<string, never>, number, { id: number }, endpoints.typed_errors.ApiError>>} */ (
+            request("none", "GET", "/users/:id", "json", { typedErrors: true })
+          )
+        "###);
+        insta::assert_snapshot!(
+            serde_json::to_string_pretty(&get_user.openapi_operation()["responses"]).unwrap(),
+            @r###"
+        {
+          "200": {
+            "content": {
+              "application/json": {
+                "schema": {
+                  "format": "uint64",
+                  "minimum": 0,
+                  "type": "integer"
+                }
+              }
+            },
+            "description": ""
+          },
+          "default": {
+            "content": {
+              "application/json": {
+                "schema": {
+                  "$ref": "#/components/schemas/endpoints.typed_errors.ApiError"
+                }
+              }
+            },
+            "description": ""
+          }
+        }
+        "###
+        );
+    }
+}
+
+mod query_encoding {
+    use std::collections::BTreeMap;
+
+    use axum::extract::Query;
+    use serde::{de::DeserializeOwned, Deserialize, Serialize};
+    use tapi::{
+        endpoints::{Endpoint, Endpoints},
+        Tapi,
+    };
+
+    /// Runs the `encodeForm` of the prelude on `query`, or returns `None` if
+    /// `node` is not installed.
+    fn encode_form(query: &serde_json::Value) -> Option<String> {
+        let prelude = include_str!("../src/targets/prelude.js");
+        let script = format!("{prelude}\nprocess.stdout.write(encodeForm({query}));");
+        let output = match std::process::Command::new("node")
+            .args(["--input-type=module", "--eval", &script])
+            .output()
+        {
+            Ok(output) => output,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return None,
+            Err(err) => panic!("failed to run node: {err}"),
+        };
+        assert!(
+            output.status.success(),
+            "{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        Some(String::from_utf8(output.stdout).unwrap())
+    }
+
+    /// Checks that `query`, encoded by the client, is decoded by `Query`.
+    fn roundtrip<T: Serialize + DeserializeOwned + PartialEq + std::fmt::Debug>(query: T) {
+        let Some(encoded) = encode_form(&serde_json::to_value(&query).unwrap()) else {
+            eprintln!("node is not installed, skipping the client side");
+            return;
+        };
+        let uri = format!("/search?{encoded}").parse().unwrap();
+        let Query(decoded) = Query::<T>::try_from_uri(&uri).unwrap();
+        assert_eq!(decoded, query, "decoding {encoded:?}");
+    }
+
+    #[derive(Tapi, Debug, PartialEq, Serialize, Deserialize)]
+    pub enum Order {
+        Newest,
+        Oldest,
+    }
+    #[derive(Tapi, Debug, PartialEq, Serialize, Deserialize)]
+    pub struct Search {
+        q: String,
+        page: Option<u32>,
+        cursor: Option<String>,
+        order: Order,
+        exact: bool,
+    }
+    #[derive(Tapi, Debug, PartialEq, Serialize, Deserialize)]
+    #[serde(tag = "by")]
+    pub enum Filter {
+        Author { name: String },
+        Tag { tag: String },
+    }
+    #[derive(Tapi, Debug, PartialEq, Serialize, Deserialize)]
+    pub struct Filtered {
+        q: String,
+        #[serde(flatten)]
+        filter: Filter,
+    }
+
+    #[derive(Tapi, Debug, PartialEq, Serialize, Deserialize)]
+    pub struct Labeled {
+        q: String,
+        #[serde(flatten)]
+        labels: BTreeMap<String, String>,
+    }
+
+    #[derive(Tapi, Deserialize)]
+    pub struct Tags {
+        tags: Vec<String>,
+    }
+    #[derive(Tapi, Deserialize)]
+    pub struct Page {
+        page: u32,
+    }
+    #[derive(Tapi, Deserialize)]
+    pub struct Paged {
+        q: String,
+        #[serde(flatten)]
+        page: Page,
+    }
+    #[derive(Tapi, Deserialize)]
+    pub enum Sort {
+        By(String),
+    }
+    #[derive(Tapi, Deserialize)]
+    pub struct Sorted {
+        sort: Sort,
+    }
+
+    #[tapi::tapi(path = "/search", method = Get)]
+    async fn search(Query(_): Query<Search>) -> String {
+        String::new()
+    }
+    #[tapi::tapi(path = "/params", method = Get)]
+    async fn params(Query(query): Query<BTreeMap<String, u32>>) -> String {
+        query.len().to_string()
+    }
+    #[tapi::tapi(path = "/labeled", method = Get)]
+    async fn labeled(Query(query): Query<Labeled>) -> String {
+        format!("{} {}", query.q, query.labels.len())
+    }
+    #[tapi::tapi(path = "/tags", method = Get)]
+    async fn tags(Query(query): Query<Tags>) -> String {
+        query.tags.join(",")
+    }
+    #[tapi::tapi(path = "/paged", method = Get)]
+    async fn paged(Query(query): Query<Paged>) -> String {
+        format!("{} {}", query.q, query.page.page)
+    }
+    #[tapi::tapi(path = "/sorted", method = Post)]
+    async fn sorted(axum::Form(form): axum::Form<Sorted>) -> String {
+        let Sort::By(by) = form.sort;
+        by
+    }
+
+    #[test]
+    fn query_encoding() {
+        roundtrip(Search {
+            q: "rust & ts".to_string(),
+            page: Some(2),
+            cursor: None,
+            order: Order::Newest,
+            exact: true,
+        });
+        roundtrip(vec![
+            ("tag".to_string(), "a".to_string()),
+            ("tag".to_string(), "b c".to_string()),
+        ]);
+        roundtrip(Filtered {
+            q: "rust".to_string(),
+            filter: Filter::Tag {
+                tag: "serde".to_string(),
+            },
+        });
+
+        roundtrip(BTreeMap::from([
+            ("page".to_string(), 2),
+            ("per page".to_string(), 20),
+        ]));
+        roundtrip(Labeled {
+            q: "rust".to_string(),
+            labels: BTreeMap::from([("lang".to_string(), "en & de".to_string())]),
+        });
+
+        let search: &dyn Endpoint<()> = &search::endpoint;
+        insta::assert_snapshot!(search.ts_client(), @r###"request<endpoints.query_encoding.SearchInput, string>("query", "GET", "/search", "text")"###);
+        let params: &dyn Endpoint<()> = &params::endpoint;
+        let labeled: &dyn Endpoint<()> = &labeled::endpoint;
+        assert!(Endpoints::new([search, params, labeled])
+            .ts_client()
+            .is_ok());
+
+        let error = |endpoint: &dyn Endpoint<()>| {
+            Endpoints::new([endpoint])
+                .ts_client()
+                .unwrap_err()
+                .to_string()
+        };
+        insta::assert_snapshot!(error(&tags::endpoint), @"field `tags` of `endpoints::query_encoding::Tags` cannot be encoded in a query string or form, which only holds scalar values");
+        insta::assert_snapshot!(error(&paged::endpoint), @"field `page` of `endpoints::query_encoding::Page` is flattened or in an enum, where a query string or form only holds strings");
+        insta::assert_snapshot!(error(&sorted::endpoint), @"field `sort` of `endpoints::query_encoding::Sorted` cannot be encoded in a query string or form, which only holds scalar values");
+    }
+}
+
+mod router_state {
+    use axum::{extract::State, Router};
+    use tapi::endpoints::Endpoint;
+
+    #[derive(Clone)]
+    pub struct Users {
+        name: String,
+    }
+    #[derive(Clone)]
+    pub struct Billing {
+        currency: &'static str,
+    }
+
+    #[tapi::tapi(path = "/user", method = Get)]
+    async fn user(State(users): State<Users>) -> String {
+        users.name
+    }
+    #[tapi::tapi(path = "/currency", method = Get)]
+    async fn currency(State(billing): State<Billing>) -> String {
+        billing.currency.to_string()
+    }
+    #[tapi::tapi(path = "/invoices", method = Get, state = crate::router_state::Billing)]
+    async fn invoices() -> String {
+        String::new()
+    }
+    #[tapi::tapi(path = "/health", method = Get)]
+    async fn health() -> String {
+        String::new()
+    }
+
+    #[test]
+    fn router_state() {
+        let users: [&dyn Endpoint<Users>; 2] = [&user::endpoint, &health::endpoint];
+        let billing: [&dyn Endpoint<Billing>; 3] =
+            [&currency::endpoint, &invoices::endpoint, &health::endpoint];
+
+        let _: Router = Router::new()
+            .merge(
+                users
+                    .iter()
+                    .fold(Router::new(), |router, e| e.bind_to(router))
+                    .with_state(Users {
+                        name: "ferris".to_string(),
+                    }),
+            )
+            .nest(
+                "/billing",
+                billing
+                    .iter()
+                    .fold(Router::new(), |router, e| e.bind_to(router))
+                    .with_state(Billing { currency: "EUR" }),
+            );
+
+        assert_eq!(users.map(|e| e.path()), ["/user", "/health"]);
+        assert_eq!(
+            billing.map(|e| e.path()),
+            ["/currency", "/invoices", "/health"]
+        );
+    }
+}
+
+#[cfg(feature = "tracing")]
+mod tracing {
+    use axum::extract::Path;
+    use futures_util::FutureExt;
+    use tapi::endpoints::Endpoint;
+
+    #[tapi::tapi(path = "/users", method = Get, tracing)]
+    async fn users() -> String {
+        "users".to_string()
+    }
+    #[tapi::tapi(
+        path = "/users/:id",
+        method = [Get, Head],
+        tracing(name = "user", fields(user.id = id, admin = tapi::tracing::field::Empty)),
+    )]
+    async fn user(Path(id): Path<u64>) -> String {
+        format!("user {id}")
+    }
+
+    #[test]
+    fn tracing() {
+        assert_eq!(users().now_or_never().unwrap(), "users");
+        assert_eq!(user(Path(7)).now_or_never().unwrap(), "user 7");
+
+        let endpoints: Vec<&dyn Endpoint<()>> = user::endpoints();
+        assert_eq!(endpoints.len(), 2);
+        assert_eq!(Endpoint::<()>::path(&users::endpoint), "/users");
+    }
+}
+
+mod status_responses {
+    use axum::{
+        extract::Path,
+        http::{HeaderMap, StatusCode},
+        response::{IntoResponse, Redirect},
+        Json,
+    };
+    use tapi::{
+        endpoints::{Endpoint, ResponseTapi, ResponseTapiExtractor},
+        Tapi,
+    };
+
+    #[derive(Tapi, serde::Serialize, serde::Deserialize)]
+    pub struct User {
+        name: String,
+    }
+    #[derive(Tapi, serde::Serialize)]
+    pub struct NotFound {
+        id: u64,
+    }
+
+    #[tapi::tapi(
+        path = "/users/:id",
+        method = Get,
+        responses(200 = Json<User>, 404 = Json<NotFound>, 410 = String),
+    )]
+    async fn get_user(Path(id): Path<u64>) -> impl IntoResponse {
+        match id {
+            0 => (StatusCode::GONE, "deleted".to_string()).into_response(),
+            1 => Json(User {
+                name: "ferris".to_string(),
+            })
+            .into_response(),
+            _ => (StatusCode::NOT_FOUND, Json(NotFound { id })).into_response(),
+        }
+    }
+    #[tapi::tapi(path = "/users", method = Post)]
+    async fn create_user(Json(user): Json<User>) -> (StatusCode, HeaderMap, Json<User>) {
+        (StatusCode::CREATED, HeaderMap::new(), Json(user))
+    }
+
+    #[test]
+    fn status_responses() {
+        assert!(matches!(Redirect::extract_response(), ResponseTapi::None));
+        assert!(matches!(
+            <(HeaderMap, Json<User>)>::extract_response(),
+            ResponseTapi::Json(_)
+        ));
+
+        let get_user: &dyn Endpoint<()> = &get_user::endpoint;
+        let create_user: &dyn Endpoint<()> = &create_user::endpoint;
+        insta::assert_snapshot!(get_user.ts_client(), @r###"requestStatus<Record<string, never>, { status: 200; data: endpoints.status_responses.User } | { status: 404; data: endpoints.status_responses.NotFound } | { status: 410; data: string }, { id: number }>("none", "GET", "/users/:id", { 200: "json", 404: "json", 410: "text" })"###);
+        insta::assert_snapshot!(get_user.js_client(), @r###"
+        /** @type {ReturnType<typeof requestStatus<Record<string, never>, { status: 200; data: endpoints.status_responses.User } | { status: 404; data: endpoints.status_responses.NotFound } | { status: 410; data: string }, { id: number }>>} */ (
+            requestStatus("none", "GET", "/users/:id", { 200: "json", 404: "json", 410: "text" })
+          )
+        "###);
+        insta::assert_snapshot!(
+            serde_json::to_string_pretty(&get_user.openapi_operation()["responses"]).unwrap(),
+            @r###"
+        {
+          "200": {
+            "content": {
+              "application/json": {
+                "schema": {
+                  "$ref": "#/components/schemas/endpoints.status_responses.User"
+                }
+              }
+            },
+            "description": ""
+          },
+          "404": {
+            "content": {
+              "application/json": {
+                "schema": {
+                  "$ref": "#/components/schemas/endpoints.status_responses.NotFound"
+                }
+              }
+            },
+            "description": ""
+          },
+          "410": {
+            "content": {
+              "text/plain": {
+                "schema": {
+                  "type": "string"
+                }
+              }
+            },
+            "description": ""
+          }
+        }
+        "###
+        );
+        insta::assert_snapshot!(create_user.ts_client(), @r###"request<endpoints.status_responses.User, endpoints.status_responses.User>("json", "POST", "/users", "json")"###);
+    }
+}
+
+mod client_names {
+    use axum::extract::Path;
+    use tapi::endpoints::{Endpoint, Endpoints};
+
+    #[tapi::tapi(path = "/", method = Get)]
+    async fn index() -> String {
+        String::new()
+    }
+    #[tapi::tapi(path = "/users", method = Get)]
+    async fn list_users() -> String {
+        String::new()
+    }
+    #[tapi::tapi(path = "/users", method = Post)]
+    async fn create_user() -> String {
+        String::new()
+    }
+    #[tapi::tapi(path = "/users/:id", method = Delete)]
+    async fn delete_user(Path(id): Path<u64>) -> String {
+        id.to_string()
+    }
+    #[tapi::tapi(path = "/users/:id", method = Get, name = "user")]
+    async fn get_user(Path(id): Path<u64>) -> String {
+        id.to_string()
+    }
+    #[tapi::tapi(path = "/users", method = Get)]
+    async fn all_users() -> String {
+        String::new()
+    }
+
+    /// The `api` object of the TypeScript client.
+    fn api(endpoints: &[&dyn Endpoint<()>]) -> Result<String, tapi::Error> {
+        let client = Endpoints::new(endpoints.iter().copied()).ts_client()?;
+        Ok(client[client.find("export const api").unwrap()..].to_string())
+    }
+
+    #[test]
+    fn client_names() {
+        let endpoints: [&dyn Endpoint<()>; 5] = [
+            &index::endpoint,
+            &list_users::endpoint,
+            &create_user::endpoint,
+            &delete_user::endpoint,
+            &get_user::endpoint,
+        ];
+        insta::assert_snapshot!(api(&endpoints).unwrap(), @r###"
+        export const api = {
+            get: request<Record<string, never>, string>("none", "GET", "/", "text"),
+            getUsers: request<Record<string, never>, string>("none", "GET", "/users", "text"),
+            postUsers: request<Record<string, never>, string>("none", "POST", "/users", "text"),
+            deleteUsersId: request<Record<string, never>, string, { id: number }>("none", "DELETE", "/users/:id", "text"),
+            user: request<Record<string, never>, string, { id: number }>("none", "GET", "/users/:id", "text"),
+        };
+        "###);
+    }
+
+    #[test]
+    fn client_names_duplicate() {
+        let err = api(&[&list_users::endpoint, &all_users::endpoint]).unwrap_err();
+        insta::assert_snapshot!(err, @r###"endpoints `GET /users` and `GET /users` are both named `getUsers`, use `#[tapi(name = "...")]` to tell them apart"###);
+    }
+}
+
+mod client_layouts {
+    use axum::extract::Path;
+    use tapi::endpoints::{ClientLayout, Endpoint, Endpoints};
+
+    #[tapi::tapi(path = "/", method = Get)]
+    async fn index() -> String {
+        String::new()
+    }
+    #[tapi::tapi(path = "/users", method = Get, group = "users")]
+    async fn list_users() -> String {
+        String::new()
+    }
+    #[tapi::tapi(path = "/users", method = Post, group = "users")]
+    async fn create_user() -> String {
+        String::new()
+    }
+    #[tapi::tapi(path = "/users/:id", method = Get, group = "users", name = "byId")]
+    async fn get_user(Path(id): Path<u64>) -> String {
+        id.to_string()
+    }
+    #[tapi::tapi(path = "/admin/users/*path", method = Delete, group = "admin.users")]
+    async fn delete_users(Path(path): Path<String>) -> String {
+        path
+    }
+    #[tapi::tapi(path = "/get", method = Get)]
+    async fn get() -> String {
+        String::new()
+    }
+
+    /// The `api` object of the JavaScript client.
+    fn api(endpoints: &[&dyn Endpoint<()>], layout: ClientLayout) -> Result<String, tapi::Error> {
+        let client = Endpoints::new(endpoints.iter().copied())
+            .with_layout(layout)
+            .js_client()?;
+        Ok(client[client.find("export const api").unwrap()..].to_string())
+    }
+
+    #[test]
+    fn client_layouts() {
+        let endpoints: [&dyn Endpoint<()>; 5] = [
+            &index::endpoint,
+            &list_users::endpoint,
+            &create_user::endpoint,
+            &get_user::endpoint,
+            &delete_users::endpoint,
+        ];
+        insta::assert_snapshot!(api(&endpoints, ClientLayout::Path).unwrap(), @r###"
+        export const api = {
+          get: /** @type {ReturnType<typeof request<Record<string, never>, string>>} */ (
+            request("none", "GET", "/", "text")
+          ),
+          users: {
+            get: /** @type {ReturnType<typeof request<Record<string, never>, string>>} */ (
+              request("none", "GET", "/users", "text")
+            ),
+            post: /** @type {ReturnType<typeof request<Record<string, never>, string>>} */ (
+              request("none", "POST", "/users", "text")
+            ),
+            byId: {
+              get: /** @type {ReturnType<typeof request<Record<string, never>, string, { id: number }>>} */ (
+                request("none", "GET", "/users/:id", "text")
+              ),
+            },
+          },
+          admin: {
+            users: {
+              byPath: {
+                delete: /** @type {ReturnType<typeof request<Record<string, never>, string, { path: string }>>} */ (
+                  request("none", "DELETE", "/admin/users/*path", "text")
+                ),
+              },
+            },
+          },
+        };
+        "###);
+        insta::assert_snapshot!(api(&endpoints, ClientLayout::Group).unwrap(), @r###"
+        export const api = {
+          get: /** @type {ReturnType<typeof request<Record<string, never>, string>>} */ (
+            request("none", "GET", "/", "text")
+          ),
+          users: {
+            getUsers: /** @type {ReturnType<typeof request<Record<string, never>, string>>} */ (
+              request("none", "GET", "/users", "text")
+            ),
+            postUsers: /** @type {ReturnType<typeof request<Record<string, never>, string>>} */ (
+              request("none", "POST", "/users", "text")
+            ),
+            byId: /** @type {ReturnType<typeof request<Record<string, never>, string, { id: number }>>} */ (
+              request("none", "GET", "/users/:id", "text")
+            ),
+          },
+          admin: {
+            users: {
+              deleteAdminUsersPath: /** @type {ReturnType<typeof request<Record<string, never>, string, { path: string }>>} */ (
+                request("none", "DELETE", "/admin/users/*path", "text")
+              ),
+            },
+          },
+        };
+        "###);
+    }
+
+    #[test]
+    fn client_layouts_conflict() {
+        let err = api(&[&index::endpoint, &get::endpoint], ClientLayout::Path).unwrap_err();
+        insta::assert_snapshot!(err, @"client key `api.get.get` of `GET /get` is already taken");
+    }
+}
+
+mod openapi_document {
+    use axum::{
+        extract::{Path, Query},
+        Json,
+    };
+    use tapi::{
+        endpoints::{Endpoint, Endpoints},
+        Tapi,
+    };
+
+    #[derive(Tapi, serde::Serialize, serde::Deserialize)]
+    pub struct Person {
+        name: String,
+        age: u8,
+    }
+    #[derive(Tapi, serde::Deserialize)]
+    pub struct Filter {
+        name: Option<String>,
+    }
+
+    #[tapi::tapi(path = "/people/:id", method = Get)]
+    async fn get_person(Path(id): Path<u64>) -> Json<Person> {
+        Json(Person {
+            name: id.to_string(),
+            age: 0,
+        })
+    }
+    #[tapi::tapi(path = "/people", method = Get)]
+    async fn list_people(Query(filter): Query<Filter>) -> Json<Vec<Person>> {
+        Json(
+            filter
+                .name
+                .map(|name| Person { name, age: 0 })
+                .into_iter()
+                .collect(),
+        )
+    }
+    #[tapi::tapi(path = "/people", method = Post)]
+    async fn create_person(Json(_): Json<Person>) {}
+
+    #[derive(Tapi, serde::Deserialize)]
+    pub struct Page {
+        cursor: String,
+        #[serde(default)]
+        size: Option<u32>,
+    }
+    #[derive(Tapi, serde::Deserialize)]
+    #[serde(tag = "by")]
+    pub enum Sort {
+        Name,
+        Age,
+    }
+    #[derive(Tapi, serde::Deserialize)]
+    pub struct Search {
+        q: String,
+        #[serde(default)]
+        exact: bool,
+        #[serde(flatten)]
+        page: Page,
+        #[serde(flatten)]
+        sort: Sort,
+        #[serde(flatten)]
+        filters: std::collections::BTreeMap<String, String>,
+    }
+
+    #[tapi::tapi(path = "/people/search", method = Get)]
+    async fn search_people(Query(search): Query<Search>) -> Json<Vec<Person>> {
+        let _ = (search.q, search.exact, search.page.cursor, search.page.size);
+        let _ = (search.sort, search.filters);
+        Json(Vec::new())
+    }
+
+    #[test]
+    fn openapi_document() {
+        let endpoints = Endpoints::new([
+            &get_person::endpoint as &dyn Endpoint<()>,
+            &list_people::endpoint,
+            &create_person::endpoint,
+        ]);
+        insta::assert_snapshot!(serde_json::to_string_pretty(&endpoints.openapi("People", "1.0.0").unwrap()).unwrap(), @r###"
+        {
+          "components": {
+            "schemas": {
+              "endpoints.openapi_document.Filter": {
+                "properties": {
+                  "name": {
+                    "anyOf": [
+                      {
+                        "type": "string"
+                      },
+                      {
+                        "type": "null"
+                      }
+                    ]
+                  }
+                },
+                "required": [],
+                "type": "object"
+              },
+              "endpoints.openapi_document.Person": {
+                "properties": {
+                  "age": {
+                    "format": "uint8",
+                    "minimum": 0,
+                    "type": "integer"
+                  },
+                  "name": {
+                    "type": "string"
+                  }
+                },
+                "required": [
+                  "name",
+                  "age"
+                ],
+                "type": "object"
+              }
+            }
+          },
+          "info": {
+            "title": "People",
+            "version": "1.0.0"
+          },
+          "openapi": "3.1.0",
+          "paths": {
+            "/people": {
+              "get": {
+                "operationId": "getPeople",
+                "parameters": [
+                  {
+                    "in": "query",
+                    "name": "name",
+                    "required": false,
+                    "schema": {
+                      "anyOf": [
+                        {
+                          "type": "string"
+                        },
+                        {
+                          "type": "null"
+                        }
+                      ]
+                    }
+                  }
+                ],
+                "responses": {
+                  "200": {
+                    "content": {
+                      "application/json": {
+                        "schema": {
+                          "items": {
+                            "$ref": "#/components/schemas/endpoints.openapi_document.Person"
+                          },
+                          "type": "array"
+                        }
+                      }
+                    },
+                    "description": ""
+                  }
+                }
+              },
+              "post": {
+                "operationId": "postPeople",
+                "parameters": [],
+                "requestBody": {
+                  "content": {
+                    "application/json": {
+                      "schema": {
+                        "$ref": "#/components/schemas/endpoints.openapi_document.Person"
+                      }
+                    }
+                  },
+                  "required": true
+                },
+                "responses": {
+                  "200": {
+                    "description": ""
+                  }
+                }
+              }
+            },
+            "/people/{id}": {
+              "get": {
+                "operationId": "getPeopleId",
+                "parameters": [
+                  {
+                    "in": "path",
+                    "name": "id",
+                    "required": true,
+                    "schema": {
+                      "format": "uint64",
+                      "minimum": 0,
+                      "type": "integer"
+                    }
+                  }
+                ],
+                "responses": {
+                  "200": {
+                    "content": {
+                      "application/json": {
+                        "schema": {
+                          "$ref": "#/components/schemas/endpoints.openapi_document.Person"
+                        }
+                      }
+                    },
+                    "description": ""
+                  }
+                }
+              }
+            }
+          }
+        }
+        "###);
+    }
+
+    #[test]
+    fn query_parameters() {
+        let search: &dyn Endpoint<()> = &search_people::endpoint;
+        insta::assert_snapshot!(serde_json::to_string_pretty(&search.openapi_operation()["parameters"]).unwrap(), @r###"
+        [
+          {
+            "in": "query",
+            "name": "q",
+            "required": true,
+            "schema": {
+              "type": "string"
+            }
+          },
+          {
+            "in": "query",
+            "name": "exact",
+            "required": false,
+            "schema": {
+              "type": "boolean"
+            }
+          },
+          {
+            "in": "query",
+            "name": "cursor",
+            "required": true,
+            "schema": {
+              "type": "string"
+            }
+          },
+          {
+            "in": "query",
+            "name": "size",
+            "required": false,
+            "schema": {
+              "anyOf": [
+                {
+                  "format": "uint32",
+                  "minimum": 0,
+                  "type": "integer"
+                },
+                {
+                  "type": "null"
+                }
+              ]
+            }
+          },
+          {
+            "explode": true,
+            "in": "query",
+            "name": "sort",
+            "schema": {
+              "$ref": "#/components/schemas/endpoints.openapi_document.Sort"
+            },
+            "style": "form"
+          },
+          {
+            "explode": true,
+            "in": "query",
+            "name": "filters",
+            "schema": {
+              "additionalProperties": {
+                "type": "string"
+              },
+              "type": "object"
+            },
+            "style": "form"
+          }
+        ]
+        "###);
+    }
+
+    #[cfg(feature = "serde_yaml")]
+    #[test]
+    fn openapi_yaml() {
+        let endpoints = Endpoints::new([&get_person::endpoint as &dyn Endpoint<()>]);
+        insta::assert_snapshot!(endpoints.openapi_yaml("People", "1.0.0").unwrap(), @r###"
+        components:
+          schemas:
+            endpoints.openapi_document.Person:
+              properties:
+                age:
+                  format: uint8
+                  minimum: 0
+                  type: integer
+                name:
+                  type: string
+              required:
+              - name
+              - age
+              type: object
+        info:
+          title: People
+          version: 1.0.0
+        openapi: 3.1.0
+        paths:
+          /people/{id}:
+            get:
+              operationId: getPeopleId
+              parameters:
+              - in: path
+                name: id
+                required: true
+                schema:
+                  format: uint64
+                  minimum: 0
+                  type: integer
+              responses:
+                '200':
+                  content:
+                    application/json:
+                      schema:
+                        $ref: '#/components/schemas/endpoints.openapi_document.Person'
+                  description: ''
+        "###);
+    }
+}
+
+mod ts_client_views {
+    use axum::Json;
+    use tapi::{endpoints::Endpoint, Tapi};
+
+    #[derive(Tapi, serde::Serialize, serde::Deserialize)]
+    pub struct Person {
+        name: String,
+        #[serde(default)]
+        age: u8,
+    }
+
+    #[tapi::tapi(path = "/people", method = Post)]
+    async fn create_person(Json(person): Json<Person>) -> Json<Person> {
+        Json(person)
+    }
+
+    #[test]
+    fn ts_client_views() {
+        let create_person: &dyn Endpoint<()> = &create_person::endpoint;
+        insta::assert_snapshot!(create_person.ts_client(), @r###"request<endpoints.ts_client_views.PersonInput, endpoints.ts_client_views.Person>("json", "POST", "/people", "json")"###);
+        insta::assert_snapshot!(create_person.js_client(), @r###"
+        /** @type {ReturnType<typeof request<endpoints.ts_client_views.PersonInput, endpoints.ts_client_views.Person>>} */ (
+            request("json", "POST", "/people", "json")
+          )
+        "###);
+    }
+}
+
+mod client_path_params {
+    use axum::extract::Path;
+    use tapi::{endpoints::Endpoint, Tapi};
+
+    #[derive(Tapi, serde::Deserialize)]
+    pub struct FileParams {
+        owner: String,
+        path: String,
+    }
+
+    #[tapi::tapi(path = "/files/:id", method = Get)]
+    async fn by_id(Path(id): Path<u64>) -> String {
+        id.to_string()
+    }
+    #[tapi::tapi(path = "/files/:owner/:id", method = Get)]
+    async fn by_tuple(Path((owner, id)): Path<(String, u64)>) -> String {
+        format!("{owner}/{id}")
+    }
+    #[tapi::tapi(path = "/files/:owner/*path", method = Get)]
+    async fn by_struct(Path(params): Path<FileParams>) -> String {
+        format!("{}/{}", params.owner, params.path)
+    }
+
+    #[test]
+    fn client_path_params() {
+        let by_id: &dyn Endpoint<()> = &by_id::endpoint;
+        let by_tuple: &dyn Endpoint<()> = &by_tuple::endpoint;
+        let by_struct: &dyn Endpoint<()> = &by_struct::endpoint;
+        insta::assert_snapshot!(by_id.ts_client(), @r###"request<Record<string, never>, string, { id: number }>("none", "GET", "/files/:id", "text")"###);
+        insta::assert_snapshot!(by_tuple.ts_client(), @r###"request<Record<string, never>, string, { owner: string, id: number }>("none", "GET", "/files/:owner/:id", "text")"###);
+        insta::assert_snapshot!(by_struct.ts_client(), @r###"request<Record<string, never>, string, { owner: string, path: string }>("none", "GET", "/files/:owner/*path", "text")"###);
+        insta::assert_snapshot!(by_struct.js_client(), @r###"
+        /** @type {ReturnType<typeof request<Record<string, never>, string, { owner: string, path: string }>>} */ (
+            request("none", "GET", "/files/:owner/*path", "text")
+          )
+        "###);
+    }
+}
+
+#[cfg(all(feature = "multipart", feature = "axum-extra"))]
+mod request_bodies {
+    use axum::{body::Bytes, Form};
+    use axum_extra::{headers::UserAgent, TypedHeader};
+    use tapi::{
+        endpoints::{Endpoint, TypedMultipart},
+        Tapi,
+    };
+
+    #[derive(Tapi, serde::Deserialize)]
+    pub struct Login {
+        user: String,
+        password: String,
+    }
+    // NOTE: only describes the fields, which are read from the `Multipart`
+    #[allow(dead_code)]
+    #[derive(Tapi)]
+    pub struct Upload {
+        name: String,
+        file: Vec<u8>,
+    }
+
+    #[tapi::tapi(path = "/login", method = Post)]
+    async fn login(Form(login): Form<Login>) {
+        let _ = (login.user, login.password);
+    }
+    #[tapi::tapi(path = "/upload", method = Post)]
+    async fn upload(TypedHeader(_): TypedHeader<UserAgent>, _: TypedMultipart<Upload>) {}
+    #[tapi::tapi(path = "/raw", method = Put)]
+    async fn raw(_: Bytes) {}
+    #[tapi::tapi(path = "/text", method = Put)]
+    async fn text(_: String) {}
+
+    #[test]
+    fn request_bodies() {
+        let login: &dyn Endpoint<()> = &login::endpoint;
+        let upload: &dyn Endpoint<()> = &upload::endpoint;
+        let raw: &dyn Endpoint<()> = &raw::endpoint;
+        let text: &dyn Endpoint<()> = &text::endpoint;
+        insta::assert_snapshot!(login.ts_client(), @r###"request<endpoints.request_bodies.Login, void>("form", "POST", "/login", "none")"###);
+        insta::assert_snapshot!(upload.ts_client(), @r###"request<MultipartFields<endpoints.request_bodies.Upload>, void, { "user-agent": string }>("multipart", "POST", "/upload", "none", { headers: ["user-agent"] })"###);
+        insta::assert_snapshot!(upload.js_client(), @r###"
+        /** @type {ReturnType<typeof request<MultipartFields<endpoints.request_bodies.Upload>, void, { "user-agent": string }>>} */ (
+            request("multipart", "POST", "/upload", "none", { headers: ["user-agent"] })
+          )
+        "###);
+        insta::assert_snapshot!(raw.ts_client(), @r###"request<Blob | ArrayBuffer | Uint8Array, void>("bytes", "PUT", "/raw", "none")"###);
+        insta::assert_snapshot!(text.ts_client(), @r###"request<string, void>("text", "PUT", "/text", "none")"###);
+        insta::assert_snapshot!(
+            serde_json::to_string_pretty(&upload.openapi_operation()).unwrap(),
+            @r###"
+        {
+          "operationId": "postUpload",
+          "parameters": [
+            {
+              "in": "header",
+              "name": "user-agent",
+              "required": true,
+              "schema": {
+                "type": "string"
+              }
+            }
+          ],
+          "requestBody": {
+            "content": {
+              "multipart/form-data": {
+                "schema": {
+                  "$ref": "#/components/schemas/endpoints.request_bodies.Upload"
+                }
+              }
+            },
+            "required": true
+          },
+          "responses": {
+            "200": {
+              "description": ""
+            }
+          }
+        }
+        "###
+        );
+    }
+}
+
+mod header_params {
+    use axum::extract::{FromRequestParts, Path};
+    use futures_util::FutureExt;
+    use tapi::endpoints::{Cookie, Endpoint, Header, ParamName};
+
+    pub struct ApiKey;
+    impl ParamName for ApiKey {
+        const NAME: &'static str = "x-api-key";
+    }
+    pub struct Tenant;
+    impl ParamName for Tenant {
+        const NAME: &'static str = "tenant";
+    }
+
+    #[tapi::tapi(path = "/secret/:id", method = Get)]
+    async fn secret(
+        Path(id): Path<u32>,
+        Header(key, _): Header<ApiKey>,
+        tenant: Option<Cookie<Tenant, u64>>,
+    ) -> String {
+        let tenant = tenant.map(|Cookie(tenant, _)| tenant);
+        format!("{id} {key} {tenant:?}")
+    }
+
+    fn extract<E: FromRequestParts<()>>(
+        parts: &mut axum::http::request::Parts,
+    ) -> Result<E, E::Rejection> {
+        E::from_request_parts(parts, &()).now_or_never().unwrap()
+    }
+
+    #[test]
+    fn header_params() {
+        let (mut parts, ()) = axum::http::Request::builder()
+            .header("x-api-key", "hunter2")
+            .header("cookie", "theme=dark; tenant=42")
+            .body(())
+            .unwrap()
+            .into_parts();
+        let params = |parts: &mut axum::http::request::Parts| {
+            let Header(key, _) = extract::<Header<ApiKey>>(parts).unwrap();
+            let tenant = extract::<Option<Cookie<Tenant, u64>>>(parts).unwrap();
+            (key, tenant.map(|Cookie(tenant, _)| tenant))
+        };
+        assert_eq!(params(&mut parts), ("hunter2".to_string(), Some(42)));
+        parts.headers.remove("cookie");
+        assert_eq!(params(&mut parts), ("hunter2".to_string(), None));
+        parts.headers.remove("x-api-key");
+        assert!(extract::<Header<ApiKey>>(&mut parts).is_err());
+
+        let secret: &dyn Endpoint<()> = &secret::endpoint;
+        insta::assert_snapshot!(secret.ts_client(), @r###"request<Record<string, never>, string, { id: number, "x-api-key": string, "tenant"?: number }>("none", "GET", "/secret/:id", "text", { headers: ["x-api-key"], cookies: ["tenant"] })"###);
+        insta::assert_snapshot!(
+            serde_json::to_string_pretty(&secret.openapi_operation()["parameters"]).unwrap(),
+            @r###"
+        [
+          {
+            "in": "path",
+            "name": "id",
+            "required": true,
+            "schema": {
+              "format": "uint32",
+              "minimum": 0,
+              "type": "integer"
+            }
+          },
+          {
+            "in": "header",
+            "name": "x-api-key",
+            "required": true,
+            "schema": {
+              "type": "string"
+            }
+          },
+          {
+            "in": "cookie",
+            "name": "tenant",
+            "required": false,
+            "schema": {
+              "format": "uint64",
+              "minimum": 0,
+              "type": "integer"
+            }
+          }
+        ]
+        "###
+        );
+    }
+}
+
+mod methods {
+    use axum::extract::Path;
+    use tapi::endpoints::{Endpoint, Endpoints, Method};
+
+    #[tapi::tapi(path = "/users/:id", method = [Get, Head], name = "user")]
+    async fn user(Path(id): Path<u64>) -> String {
+        id.to_string()
+    }
+    #[tapi::tapi(path = "/users", method = [Get, Post, Options])]
+    async fn users() -> String {
+        String::new()
+    }
+
+    #[test]
+    fn methods() {
+        let endpoints: Vec<&dyn Endpoint<()>> = [user::endpoints(), users::endpoints()].concat();
+        assert_eq!(
+            endpoints.iter().map(|e| e.method()).collect::<Vec<_>>(),
+            [
+                Method::Get,
+                Method::Head,
+                Method::Get,
+                Method::Post,
+                Method::Options
+            ]
+        );
+        assert_eq!(Endpoint::<()>::method(&user::head), Method::Head);
+
+        let client = Endpoints::new(endpoints).ts_client().unwrap();
+        insta::assert_snapshot!(&client[client.find("export const api").unwrap()..], @r###"
+        export const api = {
+            getUser: request<Record<string, never>, string, { id: number }>("none", "GET", "/users/:id", "text"),
+            headUser: request<Record<string, never>, string, { id: number }>("none", "HEAD", "/users/:id", "text"),
+            getUsers: request<Record<string, never>, string>("none", "GET", "/users", "text"),
+            postUsers: request<Record<string, never>, string>("none", "POST", "/users", "text"),
+            optionsUsers: request<Record<string, never>, string>("none", "OPTIONS", "/users", "text"),
+        };
+        "###);
+    }
+}
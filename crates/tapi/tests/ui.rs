@@ -0,0 +1,12 @@
+#[test]
+fn derive() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/ui/derive/*.rs");
+}
+
+#[cfg(feature = "endpoints")]
+#[test]
+fn endpoints() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/ui/endpoints/*.rs");
+}
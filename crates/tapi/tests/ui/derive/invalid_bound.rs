@@ -0,0 +1,7 @@
+#[derive(tapi::Tapi)]
+#[tapi(bound = "T Tapi")]
+struct Wrapper<T> {
+    value: T,
+}
+
+fn main() {}
@@ -0,0 +1,7 @@
+#[derive(tapi::Tapi)]
+#[tapi(krate = "not a path")]
+struct User {
+    name: String,
+}
+
+fn main() {}
@@ -0,0 +1,5 @@
+#[derive(tapi::Tapi)]
+#[serde(tag = "type")]
+struct Point(i32, i32);
+
+fn main() {}
@@ -0,0 +1,7 @@
+#[derive(tapi::Tapi)]
+union Bits {
+    int: u32,
+    float: f32,
+}
+
+fn main() {}
@@ -0,0 +1,7 @@
+#[derive(tapi::Tapi)]
+#[tapi(rename = "Other")]
+struct User {
+    name: String,
+}
+
+fn main() {}
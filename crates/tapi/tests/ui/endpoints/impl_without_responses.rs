@@ -0,0 +1,8 @@
+use axum::response::IntoResponse;
+
+#[tapi::tapi(path = "/users", method = Get)]
+async fn users() -> impl IntoResponse {
+    String::new()
+}
+
+fn main() {}
@@ -0,0 +1,6 @@
+#[tapi::tapi(path = "/users", method = Get, responses(ok = String))]
+async fn users() -> String {
+    String::new()
+}
+
+fn main() {}
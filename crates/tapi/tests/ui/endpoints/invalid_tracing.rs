@@ -0,0 +1,6 @@
+#[tapi::tapi(path = "/users", method = Get, tracing(level = "debug"))]
+async fn users() -> String {
+    String::new()
+}
+
+fn main() {}
@@ -0,0 +1,6 @@
+#[tapi::tapi(path = "/users")]
+async fn users() -> String {
+    String::new()
+}
+
+fn main() {}
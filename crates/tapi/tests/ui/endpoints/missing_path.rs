@@ -0,0 +1,6 @@
+#[tapi::tapi(method = Get)]
+async fn users() -> String {
+    String::new()
+}
+
+fn main() {}
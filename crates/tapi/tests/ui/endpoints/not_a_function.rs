@@ -0,0 +1,4 @@
+#[tapi::tapi(path = "/users", method = Get)]
+struct Users;
+
+fn main() {}
@@ -0,0 +1,10 @@
+struct Handlers;
+
+impl Handlers {
+    #[tapi::tapi(path = "/users", method = Get)]
+    async fn users(&self) -> String {
+        String::new()
+    }
+}
+
+fn main() {}
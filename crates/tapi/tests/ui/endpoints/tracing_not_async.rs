@@ -0,0 +1,8 @@
+use std::future::{ready, Ready};
+
+#[tapi::tapi(path = "/users", method = Get, tracing)]
+fn users() -> Ready<String> {
+    ready(String::new())
+}
+
+fn main() {}
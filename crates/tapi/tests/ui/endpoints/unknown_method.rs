@@ -0,0 +1,6 @@
+#[tapi::tapi(path = "/users", method = [Get, Fetch])]
+async fn users() -> String {
+    String::new()
+}
+
+fn main() {}